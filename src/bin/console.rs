@@ -1,27 +1,34 @@
-use brrr::{self, Board, JeopardyQuestion};
-use crossterm;
-use crossterm::event::{self, read, Event, KeyCode, KeyEvent, MouseEvent};
+use brrr::{
+    self, Board, Category, Game, JeopardyQuestion, RichText, Round, RoundKind, ScoreTime, Scores,
+};
+use crossterm::event::{self, read, Event, KeyCode, KeyEvent};
 use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
 use std::sync::mpsc;
 use std::thread;
-use std::{error::Error, thread::sleep, time::Duration};
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
 use tui::{
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Span, Spans},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Terminal,
 };
 
-type TERM = Terminal<CrosstermBackend<io::Stdout>>;
+type Term = Terminal<CrosstermBackend<io::Stdout>>;
 
 fn game_selection(
     prompt: &str,
-    terminal: &mut TERM,
+    terminal: &mut Term,
     key_rx: &mpsc::Receiver<KeyEvent>,
-) -> Result<Option<usize>, Box<dyn Error>> {
+) -> Result<Option<String>, Box<dyn Error>> {
     let mut input = String::new();
+    let mut message = prompt.to_owned();
     loop {
         terminal.draw(|f| {
             let chunks = Layout::default()
@@ -47,8 +54,11 @@ fn game_selection(
                     )
                     .split(chunks[1]);
                 let prompt = Paragraph::new(vec![
-                    Spans::from(Span::from("Enter game id:")),
-                    Spans::from(Span::from(prompt)),
+                    Spans::from(Span::from(
+                        "Enter game id, #show number, air date or JSON file (esc to quit):",
+                    )),
+                    Spans::from(Span::from("(tab to browse seasons)")),
+                    Spans::from(Span::from(&message[..])),
                     Spans::from(Span::from(&input[..])),
                 ])
                 .block(Block::default().borders(Borders::ALL))
@@ -58,17 +68,21 @@ fn game_selection(
         })?;
         if let Ok(event) = key_rx.recv() {
             match event.code {
-                KeyCode::Char('q') => {
+                // not q, which can start a file name such as quiz.json
+                KeyCode::Esc => {
                     return Ok(None);
                 }
                 KeyCode::Enter => {
                     break;
                 }
                 KeyCode::Backspace => {
-                    if input.len() > 0 {
-                        input.pop();
-                    }
+                    input.pop();
                 }
+                KeyCode::Tab => match browse_seasons(terminal, key_rx)? {
+                    Ok(Some(game_id)) => return Ok(Some(game_id.to_string())),
+                    Ok(None) => {}
+                    Err(e) => message = e,
+                },
                 KeyCode::Char(x) => {
                     input.push(x);
                 }
@@ -76,8 +90,105 @@ fn game_selection(
             }
         }
     }
-    let num = input.parse::<usize>()?;
-    Ok(Some(num))
+    Ok(Some(input))
+}
+
+/// Lets the user pick one of `items` with the arrow keys and enter, or go
+/// back with esc.
+fn pick_from_list(
+    title: &str,
+    items: &[String],
+    terminal: &mut Term,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<Option<usize>> {
+    let mut state = ListState::default();
+    state.select(Some(0));
+    loop {
+        terminal.draw(|f| {
+            let list: Vec<ListItem> = items.iter().map(|item| ListItem::new(&item[..])).collect();
+            let list = List::new(list)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(Style::default().fg(Color::Blue))
+                .highlight_symbol("> ");
+            f.render_stateful_widget(list, f.size(), &mut state);
+        })?;
+        let selected = state.selected().unwrap_or(0);
+        if let Ok(event) = key_rx.recv() {
+            match event.code {
+                KeyCode::Up => state.select(Some(selected.saturating_sub(1))),
+                KeyCode::Down if selected + 1 < items.len() => state.select(Some(selected + 1)),
+                KeyCode::PageUp => state.select(Some(selected.saturating_sub(10))),
+                KeyCode::PageDown => {
+                    state.select(Some((selected + 10).min(items.len().saturating_sub(1))))
+                }
+                KeyCode::Enter if !items.is_empty() => return Ok(Some(selected)),
+                KeyCode::Esc => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Picks a game from j-archive's listings: first a season, then one of its
+/// games by air date. Errors fetching the listings are returned as a
+/// message to show.
+fn browse_seasons(
+    terminal: &mut Term,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<Result<Option<usize>, String>> {
+    let cache = brrr::Cache::default();
+    let seasons = match brrr::get_seasons(&cache) {
+        Ok(seasons) if !seasons.is_empty() => seasons,
+        Ok(_) => return Ok(Err("j-archive lists no seasons".to_owned())),
+        Err(e) => return Ok(Err(e.to_string())),
+    };
+    let names: Vec<String> = seasons
+        .iter()
+        .map(|season| match (season.first_aired(), season.last_aired()) {
+            (Some(first), Some(last)) => format!("{} ({} to {})", season.name(), first, last),
+            _ => season.name().to_owned(),
+        })
+        .collect();
+    loop {
+        let season =
+            match pick_from_list("Pick a season (esc to go back)", &names, terminal, key_rx)? {
+                Some(i) => &seasons[i],
+                None => return Ok(Ok(None)),
+            };
+        let games = match brrr::get_season_games(season.id(), &cache) {
+            Ok(games) => games,
+            Err(e) => return Ok(Err(e.to_string())),
+        };
+        let dates: Vec<String> = games
+            .iter()
+            .map(|game| {
+                let air_date = game.air_date().map(|d| d.to_string()).unwrap_or_default();
+                let show = game
+                    .show_number()
+                    .map(|n| format!("#{}", n))
+                    .unwrap_or_default();
+                format!("{:10}  {:6}  {}", air_date, show, game.title())
+            })
+            .collect();
+        let title = format!("{}: pick a game (esc to go back)", season.name());
+        if let Some(i) = pick_from_list(&title, &dates, terminal, key_rx)? {
+            return Ok(Ok(Some(games[i].game_id())));
+        }
+    }
+}
+
+/// Loads a game for play, with errors put the way players should see them.
+fn load_game(source: &str) -> Result<Game, String> {
+    brrr::load_game(source, &brrr::Cache::default()).map_err(|e| match e {
+        brrr::Error::GameNotFound(_)
+        | brrr::Error::ShowNotFound(_)
+        | brrr::Error::AirDateNotFound(_) => "Game not found".to_owned(),
+        brrr::Error::Network(_) | brrr::Error::HttpStatus(_) => {
+            "Could not reach j-archive, try again later".to_owned()
+        }
+        brrr::Error::GameFile(..) => "Game file not found".to_owned(),
+        e => e.to_string(),
+    })
 }
 
 struct Coords {
@@ -85,96 +196,244 @@ struct Coords {
     y: usize,
 }
 
+struct Player {
+    name: String,
+    score: isize,
+}
+
 struct GameState {
-    answered: [[bool; 6]; 5],
+    game_id: usize,           // where the game's media is cached
+    answered: Vec<Vec<bool>>, // [row][column] of the current board
+    playable: Vec<Vec<bool>>, // false where the board has no clue
     selected: Coords,
+    players: Vec<Player>,
+    control: usize,        // player who picks the next clue
+    broadcast_order: bool, // follow the original game's pick order
+    tiebreak_winner: Option<usize>,
 }
 
 impl GameState {
+    fn new(game_id: usize, players: Vec<Player>) -> Self {
+        GameState {
+            game_id,
+            answered: Vec::new(),
+            playable: Vec::new(),
+            selected: Coords { x: 0, y: 0 },
+            players,
+            control: 0,
+            broadcast_order: false,
+            tiebreak_winner: None,
+        }
+    }
+    fn new_round(&mut self, round: &Round) {
+        self.answered = vec![vec![false; round.columns()]; round.rows()];
+        self.playable = round
+            .board()
+            .iter()
+            .map(|row| row.iter().map(|slot| !slot.is_missing()).collect())
+            .collect();
+        // start on the first clue, reading left to right and top to bottom
+        let playable = &self.playable;
+        let (x, y) = (0..round.rows())
+            .flat_map(|y| (0..round.columns()).map(move |x| (x, y)))
+            .find(|&(x, y)| playable[y][x])
+            .unwrap_or((0, 0));
+        self.selected = Coords { x, y };
+        // the player in last place picks first
+        self.control = (0..self.players.len())
+            .min_by_key(|&i| self.players[i].score)
+            .unwrap_or(0);
+    }
+    /// In broadcast order, moves the selection to the earliest clue the
+    /// original contestants picked that has not been played yet.
+    fn follow_broadcast(&mut self, round: &Round) {
+        if !self.broadcast_order {
+            return;
+        }
+        let answered = &self.answered;
+        if let Some(&(x, y)) = round.pick_order().iter().find(|&&(x, y)| !answered[y][x]) {
+            self.selected = Coords { x, y };
+        }
+    }
+    /// Moves the selection by `(dx, dy)` until it lands on a clue, skipping
+    /// spots with none. Stays put if there is no clue that way.
+    fn step(&mut self, dx: isize, dy: isize) {
+        let (mut x, mut y) = (self.selected.x as isize, self.selected.y as isize);
+        loop {
+            x += dx;
+            y += dy;
+            let in_board = y >= 0
+                && (y as usize) < self.playable.len()
+                && x >= 0
+                && (x as usize) < self.playable[0].len();
+            if !in_board {
+                return;
+            }
+            if self.playable[y as usize][x as usize] {
+                self.selected = Coords {
+                    x: x as usize,
+                    y: y as usize,
+                };
+                return;
+            }
+        }
+    }
     fn up(&mut self) {
-        self.selected.y -= if self.selected.y > 0 { 1 } else { 0 };
+        self.step(0, -1);
     }
     fn down(&mut self) {
-        self.selected.y += if self.selected.y < 4 { 1 } else { 0 };
+        self.step(0, 1);
     }
     fn left(&mut self) {
-        self.selected.x -= if self.selected.x > 0 { 1 } else { 0 };
+        self.step(-1, 0);
     }
     fn right(&mut self) {
-        self.selected.x += if self.selected.x < 5 { 1 } else { 0 };
+        self.step(1, 0);
     }
 }
 
-impl Default for GameState {
-    fn default() -> Self {
-        GameState {
-            answered: [[false; 6]; 5],
-            selected: Coords { x: 0, y: 0 },
+fn dollars(amount: isize) -> String {
+    if amount < 0 {
+        format!("-${}", -amount)
+    } else {
+        format!("${}", amount)
+    }
+}
+
+const MAX_PLAYERS: usize = 9;
+
+fn read_players(
+    terminal: &mut Term,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<Option<Vec<Player>>> {
+    let mut players: Vec<Player> = Vec::new();
+    let mut input = String::new();
+    loop {
+        let mut text =
+            String::from("Enter player names (empty name to start, esc to go back):\n\n");
+        for (i, player) in players.iter().enumerate() {
+            text.push_str(&format!("{}. {}\n", i + 1, player.name));
+        }
+        if players.len() < MAX_PLAYERS {
+            text.push_str(&format!("{}. {}", players.len() + 1, input));
+        }
+        render_textbox(&text[..], terminal)?;
+        if let Ok(event) = key_rx.recv() {
+            match event.code {
+                KeyCode::Esc => {
+                    return Ok(None);
+                }
+                KeyCode::Enter => {
+                    let name = input.trim();
+                    if name.is_empty() || players.len() == MAX_PLAYERS {
+                        if !players.is_empty() {
+                            return Ok(Some(players));
+                        }
+                    } else {
+                        players.push(Player {
+                            name: name.to_owned(),
+                            score: 0,
+                        });
+                    }
+                    input.clear();
+                }
+                KeyCode::Backspace => {
+                    if input.is_empty() {
+                        players.pop();
+                    } else {
+                        input.pop();
+                    }
+                }
+                KeyCode::Char(x) => {
+                    input.push(x);
+                }
+                _ => {}
+            }
         }
     }
 }
 
+/// Draws the board, with `popup` over the middle of it if there is one.
 fn display_board(
-    categories: &[String],
+    categories: &[Category],
     board: &Board,
-    terminal: &mut TERM,
+    terminal: &mut Term,
     state: &GameState,
+    popup: Option<&str>,
 ) -> crossterm::Result<()> {
     terminal.draw(|f| {
+        let areas = Layout::default()
+            .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+            .split(f.size());
+
+        // scoreboard strip
+        let n = state.players.len() as u32;
+        let constraints: Vec<Constraint> = (0..n).map(|_| Constraint::Ratio(1, n)).collect();
         let chunks = Layout::default()
-            .constraints(
-                [
-                    Constraint::Percentage(16),
-                    Constraint::Percentage(16),
-                    Constraint::Percentage(16),
-                    Constraint::Percentage(16),
-                    Constraint::Percentage(16),
-                    Constraint::Percentage(16),
-                ]
-                .as_ref(),
-            )
+            .constraints(constraints)
             .direction(Direction::Horizontal)
-            .split(f.size());
+            .split(areas[1]);
+        for (i, player) in state.players.iter().enumerate() {
+            let text = format!("{}: {}", player.name, dollars(player.score));
+            let block = if i == state.control {
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Blue))
+            } else {
+                Block::default().borders(Borders::ALL)
+            };
+            let text = Paragraph::new(text)
+                .block(block)
+                .alignment(Alignment::Center);
+            f.render_widget(text, chunks[i]);
+        }
 
-        for i in 0..6 {
+        let columns = categories.len();
+        let rows = board.len();
+        let constraints: Vec<Constraint> = (0..columns)
+            .map(|_| Constraint::Ratio(1, columns as u32))
+            .collect();
+        let chunks = Layout::default()
+            .constraints(constraints)
+            .direction(Direction::Horizontal)
+            .split(areas[0]);
+
+        // category titles take a tenth of the height, clues share the rest
+        let mut constraints = vec![Constraint::Percentage(10)];
+        constraints.extend((0..rows).map(|_| Constraint::Percentage(90 / rows as u16)));
+        for i in 0..columns {
             let chunks = Layout::default()
                 .margin(1)
-                .constraints(
-                    [
-                        Constraint::Percentage(10),
-                        Constraint::Percentage(18),
-                        Constraint::Percentage(18),
-                        Constraint::Percentage(18),
-                        Constraint::Percentage(18),
-                        Constraint::Percentage(18),
-                    ]
-                    .as_ref(),
-                )
+                .constraints(constraints.clone())
                 .split(chunks[i]);
-            let title = (&categories[i]).to_owned();
+            let title = categories[i].name().to_owned();
             let title = Paragraph::new(title)
                 .block(Block::default().borders(Borders::ALL))
                 .style(Style::default().add_modifier(Modifier::BOLD))
                 .wrap(Wrap { trim: false })
                 .alignment(Alignment::Center);
             f.render_widget(title, chunks[0]);
-            for j in 0..5 {
+            for j in 0..rows {
                 // prints $value if question is valid and not answered
-                let text = if !state.answered[j][i] && board[j][i].value() != 0 {
-                    Spans::from(vec![Span::from(format!(
-                        "${}",
-                        board[j][i].value().to_string()
-                    ))])
-                } else {
-                    Spans::from(vec![Span::from("")])
+                let text = match board[j][i].clue() {
+                    Some(question) if !state.answered[j][i] => {
+                        Spans::from(vec![Span::from(format!("${}", question.value()))])
+                    }
+                    _ => Spans::from(vec![Span::from("")]),
                 };
                 let text = if state.selected.x == i && state.selected.y == j {
+                    // yellow when the selection follows the original pick order
+                    let color = if state.broadcast_order {
+                        Color::Yellow
+                    } else {
+                        Color::Blue
+                    };
                     Paragraph::new(text)
                         .wrap(Wrap { trim: false })
                         .block(
                             Block::default()
                                 .borders(Borders::ALL)
-                                .border_style(Style::default().fg(Color::Blue)),
+                                .border_style(Style::default().fg(color)),
                         )
                         .alignment(Alignment::Center)
                 } else {
@@ -186,84 +445,355 @@ fn display_board(
                 f.render_widget(text, chunks[j + 1]);
             }
         }
-    })?;
-    Ok(())
-}
 
-fn render_textbox(text: &str, terminal: &mut TERM) -> crossterm::Result<()> {
-    terminal.draw(move |f| {
-        let chunks = Layout::default()
-            .constraints(
-                [
-                    Constraint::Percentage(15),
-                    Constraint::Percentage(70),
-                    Constraint::Percentage(15),
-                ]
-                .as_ref(),
-            )
-            .split(f.size());
-        {
-            let chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(
-                    [
-                        Constraint::Percentage(15),
-                        Constraint::Percentage(70),
-                        Constraint::Percentage(15),
-                    ]
-                    .as_ref(),
-                )
-                .split(chunks[1]);
-            let text = Paragraph::new(text)
+        if let Some(popup) = popup {
+            let area = centered(f.size());
+            let text = Paragraph::new(popup)
                 .block(Block::default().borders(Borders::ALL))
                 .wrap(Wrap { trim: false })
                 .alignment(Alignment::Center);
-            f.render_widget(Clear, chunks[1]);
-            f.render_widget(text, chunks[1]);
+            f.render_widget(Clear, area);
+            f.render_widget(text, area);
         }
     })?;
     Ok(())
 }
 
-fn display_clue(
-    question: &JeopardyQuestion,
-    terminal: &mut TERM,
+/// The host's comments on a round's categories, for a popup when the board
+/// first appears. `None` if there are none.
+fn category_comments(categories: &[Category]) -> Option<String> {
+    let comments: Vec<String> = categories
+        .iter()
+        .filter_map(|category| {
+            let comment = category.comment()?;
+            Some(format!("{}\n{}", category.name(), comment))
+        })
+        .collect();
+    if comments.is_empty() {
+        None
+    } else {
+        Some(comments.join("\n\n"))
+    }
+}
+
+/// Heading for a single-clue round: its title, the category, and the
+/// host's comment on it if there is one.
+fn category_header(title: &str, category: &Category) -> String {
+    match category.comment() {
+        Some(comment) => format!("{}\n\n{}\n{}", title, category.name(), comment),
+        None => format!("{}\n\n{}", title, category.name()),
+    }
+}
+
+/// Clue or response text, with j-archive's italics, underlines and bold kept.
+fn rich_text(text: &RichText) -> Spans<'_> {
+    let spans: Vec<Span> = text
+        .spans()
+        .iter()
+        .map(|span| {
+            let style = span.style();
+            let mut modifier = Modifier::empty();
+            if style.italic() {
+                modifier |= Modifier::ITALIC;
+            }
+            if style.underline() {
+                modifier |= Modifier::UNDERLINED;
+            }
+            if style.bold() {
+                modifier |= Modifier::BOLD;
+            }
+            Span::styled(span.text(), Style::default().add_modifier(modifier))
+        })
+        .collect();
+    Spans::from(spans)
+}
+
+const MEDIA_AVAILABLE: &str = "[media available, press m to open]";
+
+/// A clue's text, followed by `media` (what happened to its pictures,
+/// sounds or videos) if it has any.
+fn clue_text<'a>(question: &'a JeopardyQuestion, media: &str) -> Text<'a> {
+    let mut text = Text::from(rich_text(question.rich_clue()));
+    if !question.media().is_empty() {
+        text.extend(Text::raw(format!("\n{}", media)));
+    }
+    text
+}
+
+/// Opens a file with whatever the desktop uses for its type.
+fn open_in_viewer(path: &Path) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let mut child = command
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // reap the viewer whenever it exits
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Downloads a clue's media into the cache if needed and opens it, saying
+/// how that went.
+fn open_media(question: &JeopardyQuestion, game_id: usize) -> String {
+    let cache = brrr::Cache::default();
+    for url in question.media() {
+        let opened = brrr::get_media(game_id, url, &cache)
+            .map_err(|e| e.to_string())
+            .and_then(|path| open_in_viewer(&path).map_err(|e| e.to_string()));
+        if let Err(e) = opened {
+            return format!("[could not open media: {}]", e);
+        }
+    }
+    String::from("[media opened]")
+}
+
+/// The middle of the screen, where text boxes and popups go.
+fn centered(area: Rect) -> Rect {
+    let constraints = [
+        Constraint::Percentage(15),
+        Constraint::Percentage(70),
+        Constraint::Percentage(15),
+    ];
+    let chunks = Layout::default()
+        .constraints(constraints.as_ref())
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints.as_ref())
+        .split(chunks[1])[1]
+}
+
+fn render_textbox<'a, T: Into<Text<'a>>>(text: T, terminal: &mut Term) -> crossterm::Result<()> {
+    terminal.draw(move |f| {
+        let area = centered(f.size());
+        let text = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+            .alignment(Alignment::Center);
+        f.render_widget(Clear, area);
+        f.render_widget(text, area);
+    })?;
+    Ok(())
+}
+
+fn read_wager(
+    header: &str,
+    score: isize,
+    min_wager: usize,
+    max_wager: usize,
+    secret: bool,
+    terminal: &mut Term,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<Option<usize>> {
+    let mut input = String::new();
+    let mut msg = "";
+    loop {
+        let shown = if secret {
+            "*".repeat(input.len())
+        } else {
+            input.clone()
+        };
+        let text = format!(
+            "{}\n\nYour score: {}\nEnter your wager (${} to ${}):\n{}\n\n{}",
+            header,
+            dollars(score),
+            min_wager,
+            max_wager,
+            shown,
+            msg
+        );
+        render_textbox(&text[..], terminal)?;
+        if let Ok(event) = key_rx.recv() {
+            match event.code {
+                KeyCode::Char('q') => {
+                    return Ok(None);
+                }
+                KeyCode::Enter => match input.parse::<usize>() {
+                    Ok(wager) if (min_wager..=max_wager).contains(&wager) => {
+                        return Ok(Some(wager))
+                    }
+                    _ => {
+                        msg = "Invalid wager";
+                        input.clear();
+                    }
+                },
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(x) if x.is_ascii_digit() => {
+                    input.push(x);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Unmarked,
+    Right,
+    Wrong,
+}
+
+/// Lets the host mark each player right or wrong and applies the result.
+/// `stakes[i]` is what player `i` stands to win or lose, or `None` if they
+/// are not playing this clue.
+fn judge(
+    text: &Text,
+    stakes: &[Option<usize>],
+    terminal: &mut Term,
     state: &mut GameState,
     key_rx: &mpsc::Receiver<KeyEvent>,
 ) -> crossterm::Result<GameResult> {
+    let eligible: Vec<usize> = (0..stakes.len()).filter(|&i| stakes[i].is_some()).collect();
+    let mut marks = vec![Mark::Unmarked; stakes.len()];
     loop {
-        render_textbox(question.clue(), terminal)?;
+        let mut status = String::from("\n");
+        for &i in &eligible {
+            let stake = stakes[i].unwrap() as isize;
+            let mark = match marks[i] {
+                Mark::Unmarked => String::new(),
+                Mark::Right => format!(": right (+{})", dollars(stake)),
+                Mark::Wrong => format!(": wrong (-{})", dollars(stake)),
+            };
+            status.push_str(&format!("{}. {}{}\n", i + 1, state.players[i].name, mark));
+        }
+        match eligible.len() {
+            0 => {}
+            1 => status.push_str("\nCorrect? (y/n)"),
+            _ => status.push_str("\nPress a player's number to mark them right or wrong"),
+        }
+        let mut lines = text.clone();
+        lines.extend(Text::raw(status));
+        render_textbox(lines, terminal)?;
         if let Ok(event) = key_rx.recv() {
             match event.code {
                 KeyCode::Enter => {
-                    state.answered[state.selected.y][state.selected.x] = true;
                     break;
                 }
-                KeyCode::Char('q') => {
+                KeyCode::Char('y') if eligible.len() == 1 => {
+                    marks[eligible[0]] = Mark::Right;
+                    break;
+                }
+                KeyCode::Char('n') if eligible.len() == 1 => {
+                    marks[eligible[0]] = Mark::Wrong;
                     break;
                 }
+                KeyCode::Char(x) if x.is_ascii_digit() => {
+                    let i = (x as usize).wrapping_sub('1' as usize);
+                    if i < stakes.len() && stakes[i].is_some() {
+                        marks[i] = match marks[i] {
+                            Mark::Unmarked => Mark::Right,
+                            Mark::Right => Mark::Wrong,
+                            Mark::Wrong => Mark::Unmarked,
+                        };
+                    }
+                }
+                KeyCode::Char('q') => {
+                    return Ok(GameResult::Quit);
+                }
+                KeyCode::Delete => {
+                    // skip scoring
+                    return Ok(GameResult::Continue);
+                }
                 _ => {}
             }
         }
     }
+    for (i, mark) in marks.into_iter().enumerate() {
+        let stake = stakes[i].unwrap_or(0) as isize;
+        match mark {
+            Mark::Right => {
+                state.players[i].score += stake;
+                state.control = i;
+            }
+            Mark::Wrong => state.players[i].score -= stake,
+            Mark::Unmarked => {}
+        }
+    }
+    Ok(GameResult::Continue)
+}
+
+fn display_clue(
+    question: &JeopardyQuestion,
+    top_value: usize,
+    terminal: &mut Term,
+    state: &mut GameState,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<GameResult> {
+    let control = state.control;
+    let wager = if question.is_daily_double() {
+        let player = &state.players[control];
+        // you may always wager up to the top value on the board, even when behind
+        let max_wager = std::cmp::max(player.score, top_value as isize) as usize;
+        let header = format!("DAILY DOUBLE\n\n{}", player.name);
+        // with no money and no values on the board, $0 is all there is to wager
+        let min_wager = 5.min(max_wager);
+        match read_wager(
+            &header,
+            player.score,
+            min_wager,
+            max_wager,
+            false,
+            terminal,
+            key_rx,
+        )? {
+            Some(wager) => Some(wager),
+            None => return Ok(GameResult::Quit),
+        }
+    } else {
+        None
+    };
+    let mut media = String::from(MEDIA_AVAILABLE);
     loop {
-        render_textbox(question.answer(), terminal)?;
+        render_textbox(clue_text(question, &media), terminal)?;
         if let Ok(event) = key_rx.recv() {
             match event.code {
                 KeyCode::Enter => {
+                    state.answered[state.selected.y][state.selected.x] = true;
                     break;
                 }
+                // ends the game there; the clue is never scored
                 KeyCode::Char('q') => {
                     return Ok(GameResult::Quit);
                 }
-                KeyCode::Delete => {
-                    break;
+                KeyCode::Char('m') if !question.media().is_empty() => {
+                    media = open_media(question, state.game_id);
                 }
                 _ => {}
             }
         }
     }
-    return Ok(GameResult::Continue);
+    let n = state.players.len();
+    let (answer, stakes) = match wager {
+        // only the player who found the daily double may respond
+        Some(wager) => {
+            let mut answer = Text::from(rich_text(question.rich_answer()));
+            if let Some(original) = question.wager() {
+                answer.extend(Text::raw(format!("(Original wager: ${})", original)));
+            }
+            let stakes = (0..n)
+                .map(|i| if i == control { Some(wager) } else { None })
+                .collect();
+            (answer, stakes)
+        }
+        None => (
+            Text::from(rich_text(question.rich_answer())),
+            vec![Some(question.value()); n],
+        ),
+    };
+    judge(&answer, &stakes, terminal, state, key_rx)
 }
 
 enum GameResult {
@@ -272,24 +802,40 @@ enum GameResult {
 }
 
 fn play_board(
-    categories: &[String],
-    board: &Vec<Vec<JeopardyQuestion>>,
-    terminal: &mut TERM,
+    round: &Round,
+    terminal: &mut Term,
     state: &mut GameState,
     key_rx: &mpsc::Receiver<KeyEvent>,
 ) -> crossterm::Result<GameResult> {
+    let board = round.board();
+    let top_value = board
+        .iter()
+        .flatten()
+        .filter_map(|slot| slot.clue())
+        .map(|q| q.value())
+        .max()
+        .unwrap_or(0);
+    state.new_round(round);
+    state.follow_broadcast(round);
+    let mut popup = category_comments(round.categories());
     loop {
-        display_board(categories, &board, terminal, &state)?;
+        display_board(round.categories(), board, terminal, state, popup.as_deref())?;
         if let Ok(event) = key_rx.recv() {
+            // any key but q closes the popup
+            if popup.is_some() && event.code != KeyCode::Char('q') {
+                popup = None;
+                continue;
+            }
             match event.code {
                 KeyCode::Enter => {
-                    match display_clue(
-                        &board[state.selected.y][state.selected.x],
-                        terminal,
-                        state,
-                        key_rx,
-                    ) {
-                        Ok(GameResult::Continue) => {}
+                    // spots with no clue, or already answered, cannot be picked
+                    let (x, y) = (state.selected.x, state.selected.y);
+                    let question = match board[y][x].clue() {
+                        Some(question) if !state.answered[y][x] => question,
+                        _ => continue,
+                    };
+                    match display_clue(question, top_value, terminal, state, key_rx) {
+                        Ok(GameResult::Continue) => state.follow_broadcast(round),
                         Ok(GameResult::Quit) => return Ok(GameResult::Quit),
                         Err(e) => return Err(e),
                     }
@@ -297,6 +843,16 @@ fn play_board(
                 KeyCode::Char('q') => {
                     return Ok(GameResult::Quit);
                 }
+                KeyCode::Char('b') => {
+                    state.broadcast_order = !state.broadcast_order;
+                    state.follow_broadcast(round);
+                }
+                KeyCode::Char('c') => {
+                    let category = &round.categories()[state.selected.x];
+                    popup = category
+                        .comment()
+                        .map(|comment| format!("{}\n{}", category.name(), comment));
+                }
                 KeyCode::Up => {
                     state.up();
                 }
@@ -319,37 +875,260 @@ fn play_board(
     Ok(GameResult::Continue)
 }
 
-fn play_final_jeopardy(category: &String, final_jeopardy: &JeopardyQuestion) {}
+const FINAL_JEOPARDY_SECONDS: u64 = 30;
 
-fn mainloop(terminal: &mut TERM, key_rx: &mpsc::Receiver<KeyEvent>) -> Result<(), Box<dyn Error>> {
-    let mut state = GameState::default();
-    let mut msg = "";
+fn wait_for_enter(
+    text: &str,
+    terminal: &mut Term,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<GameResult> {
     loop {
-        let game_id = match game_selection(msg, terminal, key_rx) {
+        render_textbox(text, terminal)?;
+        if let Ok(event) = key_rx.recv() {
+            match event.code {
+                KeyCode::Enter => return Ok(GameResult::Continue),
+                KeyCode::Char('q') => return Ok(GameResult::Quit),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn play_final_jeopardy(
+    final_jeopardy: &Round,
+    terminal: &mut Term,
+    state: &mut GameState,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<GameResult> {
+    let (category, clue) = match final_jeopardy.single_clue() {
+        Some(x) => x,
+        None => return Ok(GameResult::Continue),
+    };
+    let header = category_header("FINAL JEOPARDY", category);
+    if let GameResult::Quit = wait_for_enter(&header, terminal, key_rx)? {
+        return Ok(GameResult::Quit);
+    }
+
+    // only players in the black may play final jeopardy
+    let mut stakes = Vec::new();
+    for player in &state.players {
+        if player.score <= 0 {
+            stakes.push(None);
+            continue;
+        }
+        let header = format!("{}\n\n{}", header, player.name);
+        match read_wager(
+            &header,
+            player.score,
+            0,
+            player.score as usize,
+            true,
+            terminal,
+            key_rx,
+        )? {
+            Some(wager) => stakes.push(Some(wager)),
+            None => return Ok(GameResult::Quit),
+        }
+    }
+
+    let start = Instant::now();
+    let limit = Duration::from_secs(FINAL_JEOPARDY_SECONDS);
+    let mut media = String::from(MEDIA_AVAILABLE);
+    while let Some(remaining) = limit.checked_sub(start.elapsed()) {
+        let mut text = clue_text(clue, &media);
+        text.extend(Text::raw(format!("\n{}", remaining.as_secs() + 1)));
+        render_textbox(text, terminal)?;
+        if let Ok(event) = key_rx.recv_timeout(Duration::from_millis(200)) {
+            match event.code {
+                KeyCode::Enter => break,
+                KeyCode::Char('q') => return Ok(GameResult::Quit),
+                KeyCode::Char('m') if !clue.media().is_empty() => {
+                    media = open_media(clue, state.game_id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let answer = Text::from(rich_text(clue.rich_answer()));
+    judge(&answer, &stakes, terminal, state, key_rx)
+}
+
+/// Indices of the players sharing the lead, if more than one does.
+fn tied_leaders(state: &GameState) -> Vec<usize> {
+    let top = match state.players.iter().map(|player| player.score).max() {
+        Some(x) => x,
+        None => return Vec::new(),
+    };
+    let leaders: Vec<usize> = (0..state.players.len())
+        .filter(|&i| state.players[i].score == top)
+        .collect();
+    if leaders.len() > 1 {
+        leaders
+    } else {
+        Vec::new()
+    }
+}
+
+/// Plays a tiebreaker clue between the players sharing the lead. Nobody's
+/// score changes; the first player to answer correctly wins the game.
+fn play_tiebreaker(
+    tiebreaker: &Round,
+    terminal: &mut Term,
+    state: &mut GameState,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<GameResult> {
+    let tied = tied_leaders(state);
+    let (category, clue) = match tiebreaker.single_clue() {
+        Some(x) if !tied.is_empty() => x,
+        _ => return Ok(GameResult::Continue),
+    };
+    let header = category_header("TIEBREAKER", category);
+    if let GameResult::Quit = wait_for_enter(&header, terminal, key_rx)? {
+        return Ok(GameResult::Quit);
+    }
+
+    let mut media = String::from(MEDIA_AVAILABLE);
+    loop {
+        render_textbox(clue_text(clue, &media), terminal)?;
+        if let Ok(event) = key_rx.recv() {
+            match event.code {
+                KeyCode::Enter => break,
+                KeyCode::Char('q') => return Ok(GameResult::Quit),
+                KeyCode::Char('m') if !clue.media().is_empty() => {
+                    media = open_media(clue, state.game_id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut status = String::from("\n");
+    for &i in &tied {
+        status.push_str(&format!("{}. {}\n", i + 1, state.players[i].name));
+    }
+    status.push_str("\nPress the number of the player who got it right (enter if nobody did)");
+    let mut text = Text::from(rich_text(clue.rich_answer()));
+    text.extend(Text::raw(status));
+    loop {
+        render_textbox(text.clone(), terminal)?;
+        if let Ok(event) = key_rx.recv() {
+            match event.code {
+                KeyCode::Enter => return Ok(GameResult::Continue),
+                KeyCode::Char('q') => return Ok(GameResult::Quit),
+                KeyCode::Char(x) if x.is_ascii_digit() => {
+                    let i = (x as usize).wrapping_sub('1' as usize);
+                    if tied.contains(&i) {
+                        state.tiebreak_winner = Some(i);
+                        return Ok(GameResult::Continue);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Plays every round of the game in order, showing the standings after each
+/// board. A tiebreaker is only played if the lead is shared.
+fn play_game(
+    game: &Game,
+    terminal: &mut Term,
+    state: &mut GameState,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<GameResult> {
+    for round in game.rounds() {
+        let result = match round.kind() {
+            RoundKind::Standard | RoundKind::Double => {
+                if let GameResult::Quit = play_board(round, terminal, state, key_rx)? {
+                    return Ok(GameResult::Quit);
+                }
+                let (header, time) = match round.kind() {
+                    RoundKind::Standard => ("END OF THE JEOPARDY! ROUND", ScoreTime::EndOfJeopardy),
+                    _ => (
+                        "END OF THE DOUBLE JEOPARDY! ROUND",
+                        ScoreTime::EndOfDoubleJeopardy,
+                    ),
+                };
+                display_standings(header, state, game.scores_at(time), terminal, key_rx)?
+            }
+            RoundKind::Final => play_final_jeopardy(round, terminal, state, key_rx)?,
+            RoundKind::Tiebreaker => play_tiebreaker(round, terminal, state, key_rx)?,
+        };
+        if let GameResult::Quit = result {
+            return Ok(GameResult::Quit);
+        }
+    }
+    Ok(GameResult::Continue)
+}
+
+/// Shows everyone's score, followed by what the real contestants had at the
+/// same point if the game records it.
+fn display_standings(
+    header: &str,
+    state: &GameState,
+    broadcast: Option<&Scores>,
+    terminal: &mut Term,
+    key_rx: &mpsc::Receiver<KeyEvent>,
+) -> crossterm::Result<GameResult> {
+    // the tiebreaker's winner goes ahead of the players they were tied with
+    let mut players: Vec<usize> = (0..state.players.len()).collect();
+    players.sort_by_key(|&i| (-state.players[i].score, state.tiebreak_winner != Some(i)));
+    let mut text = format!("{}\n\n", header);
+    for (rank, &i) in players.iter().enumerate() {
+        let player = &state.players[i];
+        text.push_str(&format!(
+            "{}. {}: {}\n",
+            rank + 1,
+            player.name,
+            dollars(player.score)
+        ));
+    }
+    if let Some(winner) = state.tiebreak_winner {
+        text.push_str(&format!(
+            "\n{} wins the tiebreaker\n",
+            state.players[winner].name
+        ));
+    }
+    if let Some(broadcast) = broadcast {
+        text.push_str("\nOn the broadcast:\n");
+        for score in broadcast.scores() {
+            text.push_str(&format!(
+                "{}: {}\n",
+                score.contestant(),
+                dollars(score.score())
+            ));
+        }
+    }
+    wait_for_enter(&text, terminal, key_rx)
+}
+
+fn mainloop(terminal: &mut Term, key_rx: &mpsc::Receiver<KeyEvent>) -> Result<(), Box<dyn Error>> {
+    let mut msg = String::new();
+    loop {
+        let source = match game_selection(&msg, terminal, key_rx) {
             Ok(Some(x)) => x,
             Ok(None) => break,
-            Err(_) => {
-                msg = "Invalid game id";
-                continue;
-            } // parse or input error
+            Err(_) => break, // input error
         };
-        let game_data = match brrr::get_game_data(game_id) {
-            Some(x) => x,
-            None => {
-                msg = "Game not found";
+        let game_data = match load_game(source.trim()) {
+            Ok(x) => x,
+            Err(e) => {
+                msg = e;
                 continue;
             } // game not found
         };
+        let players = match read_players(terminal, key_rx) {
+            Ok(Some(x)) => x,
+            Ok(None) => {
+                msg.clear();
+                continue;
+            }
+            _ => break,
+        };
+        let mut state = GameState::new(game_data.info().game_id(), players);
 
-        let (all_categories, board_1, board_2, final_jeopardy) = game_data;
-
-        match play_board(
-            &all_categories[..6],
-            &board_1,
-            terminal,
-            &mut state,
-            &key_rx,
-        ) {
+        match play_game(&game_data, terminal, &mut state, key_rx) {
             Ok(GameResult::Continue) => {
                 // continues
             }
@@ -359,23 +1138,21 @@ fn mainloop(terminal: &mut TERM, key_rx: &mpsc::Receiver<KeyEvent>) -> Result<()
             _ => break,
         }
 
-        match play_board(
-            &all_categories[6..12],
-            &board_2,
+        match display_standings(
+            "FINAL STANDINGS",
+            &state,
+            game_data.scores_at(ScoreTime::Final),
             terminal,
-            &mut state,
-            &key_rx,
+            key_rx,
         ) {
             Ok(GameResult::Continue) => {
-                // continues
+                msg.clear();
             }
             Ok(GameResult::Quit) => {
                 break;
             }
             _ => break,
         }
-
-        play_final_jeopardy(&all_categories[12], &final_jeopardy);
     }
     Ok(())
 }
@@ -385,14 +1162,14 @@ fn setup_input() -> crossterm::Result<mpsc::Receiver<KeyEvent>> {
     thread::spawn(move || loop {
         if event::poll(Duration::from_millis(100)).unwrap() {
             if let Ok(Event::Key(event)) = read() {
-                tx.send(event);
+                let _ = tx.send(event);
             }
         }
     });
     Ok(rx)
 }
 
-fn setup_terminal() -> crossterm::Result<TERM> {
+fn setup_terminal() -> crossterm::Result<Term> {
     let stdout = io::stdout();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
@@ -401,7 +1178,7 @@ fn setup_terminal() -> crossterm::Result<TERM> {
     Ok(terminal)
 }
 
-fn stop_terminal(terminal: &mut TERM) -> crossterm::Result<()> {
+fn stop_terminal(_terminal: &mut Term) -> crossterm::Result<()> {
     crossterm::terminal::disable_raw_mode()?;
     Ok(())
 }
@@ -415,5 +1192,5 @@ fn main() {
         Ok(()) => {}
         Err(e) => eprintln!("Error: {}", e),
     };
-    stop_terminal(&mut terminal);
+    let _ = stop_terminal(&mut terminal);
 }
@@ -0,0 +1,286 @@
+//! A stand-in for j-archive that serves saved pages on localhost, at the
+//! same urls as the real site, and misbehaves on request. Point the scraper
+//! at it with `BRRR_BASE_URL=http://127.0.0.1:<port>`.
+
+use brrr::{Cache, CacheFetcher, Error, Fetcher, FixtureFetcher};
+use std::collections::HashMap;
+use std::env::args;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long a `slow` page keeps the client waiting unless told otherwise:
+/// well past the default read timeout of brrr's crawl policy (30 seconds),
+/// so the client reliably gives up.
+const SLOW: Duration = Duration::from_secs(60);
+
+/// What j-archive answers, with a 200, for a game it doesn't have.
+const ERROR_PAGE: &str = "<html><head><title>J! Archive - ERROR</title></head>\
+<body><div id=\"content\"><p>ERROR: No game in database with this id.</p></div></body></html>";
+
+/// Ways a page can be made to misbehave.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Fault {
+    /// Answer after this long, [`SLOW`] by default.
+    Slow(Duration),
+    /// 404 Not Found.
+    Missing,
+    /// j-archive's error page, as if the game didn't exist.
+    Error,
+    /// Promise the whole body, send half of it and hang up.
+    Truncate,
+    /// 503 Service Unavailable.
+    Unavailable,
+}
+
+impl FromStr for Fault {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Fault, String> {
+        if let Some(secs) = s.strip_prefix("slow=") {
+            return secs
+                .parse()
+                .map(|secs| Fault::Slow(Duration::from_secs(secs)))
+                .map_err(|_| format!("invalid delay: {}", secs));
+        }
+        match s {
+            "slow" => Ok(Fault::Slow(SLOW)),
+            "missing" => Ok(Fault::Missing),
+            "error" => Ok(Fault::Error),
+            "truncate" => Ok(Fault::Truncate),
+            "unavailable" => Ok(Fault::Unavailable),
+            _ => Err(format!("unknown fault: {}", s)),
+        }
+    }
+}
+
+struct Server {
+    fetcher: Box<dyn Fetcher>,
+    /// Added to every response.
+    delay: Duration,
+    /// How many times each url answers 503 before it is served.
+    fail_first: usize,
+    /// Faults by page name (see [`page_name`]).
+    faults: HashMap<String, Fault>,
+    requests: Mutex<HashMap<String, usize>>,
+}
+
+struct Response {
+    status: &'static str,
+    body: Vec<u8>,
+    truncate: bool,
+}
+
+impl Response {
+    fn new(status: &'static str, body: impl Into<Vec<u8>>) -> Response {
+        Response {
+            status,
+            body: body.into(),
+            truncate: false,
+        }
+    }
+}
+
+fn query_param<'a>(target: &'a str, name: &str) -> Option<&'a str> {
+    let query = target.split('?').nth(1)?;
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        if key == name {
+            Some(value)
+        } else {
+            None
+        }
+    })
+}
+
+/// What a request is for, named the way faults are given: a game id,
+/// `seasons`, `season-<season>` or a media file name.
+fn page_name(target: &str) -> Option<String> {
+    let path = target.split('?').next()?;
+    match path {
+        "/showgame.php" => query_param(target, "game_id").map(str::to_owned),
+        "/listseasons.php" => Some("seasons".to_owned()),
+        "/showseason.php" => query_param(target, "season").map(|s| format!("season-{}", s)),
+        _ if path.starts_with("/media/") => path.rsplit('/').next().map(str::to_owned),
+        _ => None,
+    }
+}
+
+impl Server {
+    fn respond(&self, target: &str) -> Response {
+        let count = {
+            let mut requests = self.requests.lock().unwrap();
+            let count = requests.entry(target.to_owned()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        if count <= self.fail_first {
+            return Response::new("503 Service Unavailable", "try again later");
+        }
+        let page = match page_name(target) {
+            Some(page) => page,
+            None => return Response::new("404 Not Found", "no such page"),
+        };
+        match self.faults.get(&page) {
+            Some(Fault::Slow(wait)) => thread::sleep(*wait),
+            Some(Fault::Missing) => return Response::new("404 Not Found", "no such page"),
+            Some(Fault::Error) => return Response::new("200 OK", ERROR_PAGE),
+            Some(Fault::Unavailable) => {
+                return Response::new("503 Service Unavailable", "try again later")
+            }
+            Some(Fault::Truncate) | None => {}
+        }
+        // the fetchers only look at the path and query
+        let mut response = match self
+            .fetcher
+            .fetch_bytes(&format!("http://standin{}", target))
+        {
+            Ok(body) => Response::new("200 OK", body),
+            Err(Error::Offline(_)) if target.starts_with("/showgame.php") => {
+                Response::new("200 OK", ERROR_PAGE)
+            }
+            Err(Error::Offline(_)) => Response::new("404 Not Found", "no such page"),
+            Err(e) => Response::new("500 Internal Server Error", e.to_string()),
+        };
+        response.truncate = self.faults.get(&page) == Some(&Fault::Truncate);
+        response
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+        let target = request.split_whitespace().nth(1).unwrap_or("/");
+
+        thread::sleep(self.delay);
+        let response = self.respond(target);
+        eprintln!(
+            "{} {}{}",
+            target,
+            response.status,
+            if response.truncate {
+                " (truncated)"
+            } else {
+                ""
+            }
+        );
+
+        let body = if response.truncate {
+            &response.body[..response.body.len() / 2]
+        } else {
+            &response.body[..]
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            response.body.len()
+        )?;
+        stream.write_all(body)?;
+        stream.flush()?;
+        stream.shutdown(Shutdown::Both)
+    }
+}
+
+fn usage(name: &str) {
+    println!(
+        "Usage: {} [--port <port>] [--cache-dir <dir> | --fixtures <dir>] [--delay <ms>]",
+        name
+    );
+    println!("       [--fail-first <n>] [--fault <page>:<kind>]...");
+    println!("Serves pages from the cache (read only) or a fixture directory.");
+    println!("--delay waits before every response; --fail-first answers 503 to the");
+    println!("first n requests for each url. A page is a game id, seasons, season-<season>");
+    println!("or a media file name, and its kind of fault is one of slow (60 seconds,");
+    println!("or slow=<secs>), missing, error, truncate or unavailable.");
+}
+
+fn main() {
+    let a: Vec<String> = args().collect();
+    let mut port = 8000;
+    let mut fetcher: Box<dyn Fetcher> = Box::new(CacheFetcher::new(Cache::default_dir()));
+    let mut delay = Duration::from_millis(0);
+    let mut fail_first = 0;
+    let mut faults = HashMap::new();
+
+    let mut i = 1;
+    while i < a.len() {
+        let value = match a.get(i + 1) {
+            Some(value) => value,
+            None => return usage(&a[0]),
+        };
+        let ok = match &a[i][..] {
+            "--port" => value.parse().map(|p| port = p).is_ok(),
+            "--cache-dir" => {
+                fetcher = Box::new(CacheFetcher::new(value));
+                true
+            }
+            "--fixtures" => {
+                fetcher = Box::new(FixtureFetcher::new(value));
+                true
+            }
+            "--delay" => value
+                .parse()
+                .map(|ms| delay = Duration::from_millis(ms))
+                .is_ok(),
+            "--fail-first" => value.parse().map(|n| fail_first = n).is_ok(),
+            "--fault" => match value.rsplit_once(':') {
+                Some((page, kind)) => match kind.parse::<Fault>() {
+                    Ok(fault) => {
+                        faults.insert(page.to_owned(), fault);
+                        true
+                    }
+                    Err(e) => {
+                        println!("{}", e);
+                        false
+                    }
+                },
+                None => false,
+            },
+            _ => false,
+        };
+        if !ok {
+            return usage(&a[0]);
+        }
+        i += 2;
+    }
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("Could not listen on port {}: {}", port, e);
+            return;
+        }
+    };
+    let port = listener
+        .local_addr()
+        .map(|addr| addr.port())
+        .unwrap_or(port);
+    println!("Serving on http://127.0.0.1:{}", port);
+    println!("Use it with BRRR_BASE_URL=http://127.0.0.1:{}", port);
+
+    let server = Arc::new(Server {
+        fetcher,
+        delay,
+        fail_first,
+        faults,
+        requests: Mutex::new(HashMap::new()),
+    });
+    for stream in listener.incoming().flatten() {
+        let server = Arc::clone(&server);
+        thread::spawn(move || {
+            if let Err(e) = server.handle(stream) {
+                eprintln!("connection failed: {}", e);
+            }
+        });
+    }
+}
@@ -0,0 +1,196 @@
+//! On-disk cache of downloaded pages, keyed by game id.
+//!
+//! Everything for one game lives under `<dir>/games/<game id>/`: the raw
+//! page as `page.html`, the parsed game as `game.json`, and any clue media
+//! that has been downloaded in `media/`. Season listings are kept as
+//! `<dir>/seasons/<name>.html`. Anything missing is fetched by the cache's
+//! [`Fetcher`], from j-archive unless told otherwise. The cache
+//! directory is, in order of preference, whatever was passed to
+//! [`Cache::new`], `$BRRR_CACHE_DIR`, or `brrr` inside the platform cache
+//! directory (`$XDG_CACHE_HOME` or `~/.cache` on Linux).
+
+use crate::fetch::{CurlFetcher, Fetcher};
+use crate::{json, CacheFetcher, Error, FixtureFetcher, Game};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Environment variable that overrides the default cache directory.
+pub const CACHE_DIR_VAR: &str = "BRRR_CACHE_DIR";
+
+/// Environment variable that picks where pages not in the cache come from:
+/// `curl` (the default) fetches them from j-archive, `cache` only ever
+/// reads the cache directory, and anything else is a fixture directory to
+/// serve them from. See [`Cache::with_env_fetcher`].
+pub const FETCHER_VAR: &str = "BRRR_FETCHER";
+
+/// A cache directory, and the [`Fetcher`] that fills it.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    fetcher: Arc<dyn Fetcher>,
+}
+
+impl Default for Cache {
+    /// The default directory, filled by the fetcher `$BRRR_FETCHER` names.
+    fn default() -> Self {
+        Cache::new(Cache::default_dir()).with_env_fetcher()
+    }
+}
+
+impl Cache {
+    /// A cache filled from j-archive with [`CurlFetcher`].
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Cache {
+        Cache {
+            dir: dir.into(),
+            fetcher: Arc::new(CurlFetcher::default()),
+        }
+    }
+
+    /// Fills the cache from `fetcher` instead.
+    pub fn with_fetcher<F: Fetcher + 'static>(mut self, fetcher: F) -> Cache {
+        self.fetcher = Arc::new(fetcher);
+        self
+    }
+
+    /// Fills the cache from whatever `$BRRR_FETCHER` names. An unset or
+    /// empty variable leaves the fetcher alone.
+    pub fn with_env_fetcher(self) -> Cache {
+        match env::var_os(FETCHER_VAR) {
+            Some(name) if name.is_empty() || name == "curl" => self,
+            Some(name) if name == "cache" => {
+                let fetcher = CacheFetcher::new(&self.dir);
+                self.with_fetcher(fetcher)
+            }
+            Some(dir) => self.with_fetcher(FixtureFetcher::new(dir)),
+            None => self,
+        }
+    }
+
+    pub fn fetcher(&self) -> &dyn Fetcher {
+        self.fetcher.as_ref()
+    }
+
+    pub fn default_dir() -> PathBuf {
+        match env::var_os(CACHE_DIR_VAR) {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => dirs::cache_dir()
+                .map(|dir| dir.join("brrr"))
+                .unwrap_or_else(|| PathBuf::from("cache")),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn game_dir(&self, game_id: usize) -> PathBuf {
+        self.dir.join("games").join(game_id.to_string())
+    }
+
+    /// The j-archive page for a game, if it has been downloaded.
+    pub fn read_page(&self, game_id: usize) -> Result<Option<String>, Error> {
+        read(&self.game_dir(game_id).join("page.html"))
+    }
+
+    pub fn write_page(&self, game_id: usize, data: &str) -> Result<(), Error> {
+        write_atomic(&self.game_dir(game_id).join("page.html"), data.as_bytes())
+    }
+
+    /// The parsed game, if it was cached by the current
+    /// [`PARSER_VERSION`](crate::PARSER_VERSION).
+    pub fn read_parsed(&self, game_id: usize) -> Result<Option<Game>, Error> {
+        let data = read(&self.game_dir(game_id).join("game.json"))?;
+        Ok(data.as_deref().and_then(json::from_parsed_cache))
+    }
+
+    pub fn write_parsed(&self, game: &Game) -> Result<(), Error> {
+        let path = self.game_dir(game.info().game_id()).join("game.json");
+        write_atomic(&path, json::to_parsed_cache(game).as_bytes())
+    }
+
+    /// A cached listing page such as `seasons` or `season-39`, and when it
+    /// was downloaded.
+    pub fn read_listing(&self, name: &str) -> Result<Option<(String, SystemTime)>, Error> {
+        let path = self.listing_path(name);
+        let data = match read(&path)? {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+        let modified = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .map_err(Error::Cache)?;
+        Ok(Some((data, modified)))
+    }
+
+    pub fn write_listing(&self, name: &str, data: &str) -> Result<(), Error> {
+        write_atomic(&self.listing_path(name), data.as_bytes())
+    }
+
+    fn listing_path(&self, name: &str) -> PathBuf {
+        self.dir.join("seasons").join(listing_file(name))
+    }
+
+    /// Where a clue's media file is kept, named after the last part of its
+    /// url. The file may not have been downloaded yet.
+    pub fn media_path(&self, game_id: usize, url: &str) -> PathBuf {
+        self.game_dir(game_id).join("media").join(media_name(url))
+    }
+
+    pub fn write_media(&self, game_id: usize, url: &str, data: &[u8]) -> Result<(), Error> {
+        write_atomic(&self.media_path(game_id, url), data)
+    }
+}
+
+/// File name of a listing page.
+pub(crate) fn listing_file(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || "_-".contains(*c))
+        .collect();
+    format!("{}.html", name)
+}
+
+/// File name of a media file: the last part of its url, made safe.
+pub(crate) fn media_name(url: &str) -> String {
+    let name = url.rsplit('/').next().unwrap_or("");
+    let name = name.split(['?', '#']).next().unwrap_or("");
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || "._-".contains(*c))
+        .collect();
+    match name.trim_start_matches('.') {
+        "" => "media".to_owned(),
+        name => name.to_owned(),
+    }
+}
+
+pub(crate) fn read(path: &Path) -> Result<Option<String>, Error> {
+    match fs::read_to_string(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::Cache(e)),
+    }
+}
+
+/// Writes to a temporary file and renames it into place, so a crash never
+/// leaves a truncated file behind.
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> Result<(), Error> {
+    let dir = path.parent().expect("cache paths have a parent");
+    fs::create_dir_all(dir).map_err(Error::Cache)?;
+    let name = path.file_name().unwrap().to_string_lossy();
+    let tmp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+    let result = File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(Error::Cache)
+}
@@ -0,0 +1,114 @@
+use crate::AirDate;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can go wrong while loading a game.
+#[derive(Debug)]
+pub enum Error {
+    /// The request never completed (DNS, connection, timeout, ...). Worth
+    /// retrying later.
+    Network(Box<dyn std::error::Error + Send + Sync>),
+    /// The server answered with something other than 200 OK.
+    HttpStatus(u32),
+    /// An offline fetcher has no saved copy of this url.
+    Offline(String),
+    /// The page was not valid UTF-8.
+    Encoding(std::str::Utf8Error),
+    /// j-archive has no game with this id.
+    GameNotFound(usize),
+    /// j-archive lists no games for this season.
+    SeasonNotFound(String),
+    /// A j-archive listing page, such as the list of seasons, lists nothing.
+    EmptyListing(String),
+    /// No season listing has a game with this show number.
+    ShowNotFound(usize),
+    /// No season listing has a game that aired on this date.
+    AirDateNotFound(AirDate),
+    /// The page was fetched but the parser could not make sense of it.
+    /// `location` names the round or clue that failed, e.g. `clue_DJ_3_2`.
+    Parse {
+        game_id: usize,
+        location: String,
+        reason: String,
+    },
+    /// Reading or writing the local cache failed.
+    Cache(io::Error),
+    /// A JSON game file could not be read.
+    GameFile(PathBuf, io::Error),
+    /// A JSON game file was malformed or used an unsupported schema.
+    Json(serde_json::Error),
+}
+
+impl Error {
+    pub(crate) fn parse(game_id: usize, location: &str, reason: impl Into<String>) -> Error {
+        Error::Parse {
+            game_id,
+            location: location.to_owned(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Network(e) => write!(f, "network error: {}", e),
+            Error::HttpStatus(code) => write!(f, "server returned HTTP status {}", code),
+            Error::Offline(url) => write!(f, "{} is not available offline", url),
+            Error::Encoding(e) => write!(f, "page is not valid UTF-8: {}", e),
+            Error::GameNotFound(game_id) => write!(f, "game {} is not in j-archive", game_id),
+            Error::SeasonNotFound(season) => write!(f, "season {} is not in j-archive", season),
+            Error::EmptyListing(url) => write!(f, "{} lists nothing", url),
+            Error::ShowNotFound(show_number) => {
+                write!(f, "show #{} is not in j-archive", show_number)
+            }
+            Error::AirDateNotFound(air_date) => {
+                write!(f, "no game aired on {} in j-archive", air_date)
+            }
+            Error::Parse {
+                game_id,
+                location,
+                reason,
+            } => write!(
+                f,
+                "could not parse game {} at {}: {}",
+                game_id, location, reason
+            ),
+            Error::Cache(e) => write!(f, "cache error: {}", e),
+            Error::GameFile(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Json(e) => write!(f, "invalid game file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Network(e) => Some(e.as_ref()),
+            Error::Encoding(e) => Some(e),
+            Error::Cache(e) => Some(e),
+            Error::GameFile(_, e) => Some(e),
+            Error::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<curl::Error> for Error {
+    fn from(e: curl::Error) -> Error {
+        Error::Network(Box::new(e))
+    }
+}
+
+impl From<curl::MultiError> for Error {
+    fn from(e: curl::MultiError) -> Error {
+        Error::Network(Box::new(e))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
@@ -0,0 +1,388 @@
+//! Downloading pages from j-archive, one at a time or in batches.
+//!
+//! Everything that loads pages goes through a [`Fetcher`], so the network
+//! can be swapped for saved pages (see [`CacheFetcher`](crate::CacheFetcher)
+//! and [`FixtureFetcher`](crate::FixtureFetcher)). Requests to the network
+//! follow a [`CrawlPolicy`], so bulk downloads stay polite.
+
+use crate::Error;
+use curl::easy::{Easy2, Handler, WriteError};
+use curl::multi::{Easy2Handle, Multi};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    /// When each host was last sent a request, shared by every fetch in the
+    /// process so separate caches and batches can't add up to a flood.
+    static ref LAST_REQUEST: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+/// Where pages come from when they aren't in the [`Cache`](crate::Cache).
+/// Urls are always j-archive urls, as built by the library.
+pub trait Fetcher: fmt::Debug + Send + Sync {
+    /// Downloads a file as is, for media that isn't text.
+    fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, Error>;
+
+    fn fetch(&self, url: &str) -> Result<String, Error> {
+        into_string(self.fetch_bytes(url)?)
+    }
+
+    /// Fetches several pages, returning one result per url in the same
+    /// order. Unless overridden, fetches them one at a time.
+    fn fetch_all(&self, urls: &[&str], config: &BatchConfig) -> Vec<Result<String, Error>> {
+        let _ = config;
+        urls.iter().map(|url| self.fetch(url)).collect()
+    }
+}
+
+/// Fetches from j-archive itself, following its [`CrawlPolicy`]. The
+/// default.
+#[derive(Clone, Debug, Default)]
+pub struct CurlFetcher {
+    policy: CrawlPolicy,
+}
+
+impl CurlFetcher {
+    pub fn new(policy: CrawlPolicy) -> CurlFetcher {
+        CurlFetcher { policy }
+    }
+
+    pub fn policy(&self) -> &CrawlPolicy {
+        &self.policy
+    }
+}
+
+impl Fetcher for CurlFetcher {
+    fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, Error> {
+        get_bytes(url, &self.policy)
+    }
+
+    fn fetch_all(&self, urls: &[&str], config: &BatchConfig) -> Vec<Result<String, Error>> {
+        get_webpages(urls, &self.policy, config)
+    }
+}
+
+/// How politely to fetch from j-archive, for single and batch fetches
+/// alike.
+#[derive(Clone, Debug)]
+pub struct CrawlPolicy {
+    min_interval: Duration,
+    max_retries: u32,
+    backoff: Duration,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    user_agent: String,
+}
+
+impl Default for CrawlPolicy {
+    fn default() -> Self {
+        CrawlPolicy {
+            min_interval: Duration::from_secs(1),
+            max_retries: 3,
+            backoff: Duration::from_secs(2),
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            user_agent: format!(
+                "brrr/{} (jeopardy-go-brrr, a terminal Jeopardy! game)",
+                env!("CARGO_PKG_VERSION")
+            ),
+        }
+    }
+}
+
+impl CrawlPolicy {
+    /// Minimum time between starting two requests to the same host.
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+    /// How many times to retry a request that timed out or got a 5xx.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    /// Wait before the first retry, doubled for each one after it.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+    /// Longest to wait for a connection.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+    /// Longest to wait with no data arriving before giving up.
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// How long to wait before retry number `attempt` (counting from 0).
+    fn backoff_for(&self, attempt: u32) -> Duration {
+        self.backoff * 2u32.saturating_pow(attempt)
+    }
+
+    fn should_retry(&self, e: &Error, attempt: u32) -> bool {
+        attempt < self.max_retries
+            && match e {
+                Error::HttpStatus(code) => (500..600).contains(code),
+                Error::Network(e) => e
+                    .downcast_ref::<curl::Error>()
+                    .is_some_and(|e| e.is_operation_timedout()),
+                _ => false,
+            }
+    }
+}
+
+pub(crate) fn into_string(body: Vec<u8>) -> Result<String, Error> {
+    String::from_utf8(body).map_err(|e| Error::Encoding(e.utf8_error()))
+}
+
+fn host(url: &str) -> &str {
+    let rest = url.split("://").nth(1).unwrap_or(url);
+    rest.split('/').next().unwrap_or(rest)
+}
+
+/// Claims the next request to `url`'s host if `min_interval` has passed
+/// since the last one, otherwise returns how much longer to wait.
+fn claim_turn(url: &str, min_interval: Duration) -> Result<(), Duration> {
+    let mut last_request = LAST_REQUEST.lock().unwrap();
+    let now = Instant::now();
+    if let Some(last) = last_request.get(host(url)) {
+        let next = *last + min_interval;
+        if next > now {
+            return Err(next - now);
+        }
+    }
+    last_request.insert(host(url).to_owned(), now);
+    Ok(())
+}
+
+fn wait_turn(url: &str, min_interval: Duration) {
+    while let Err(wait) = claim_turn(url, min_interval) {
+        std::thread::sleep(wait);
+    }
+}
+
+struct Collector(Vec<u8>);
+
+impl Handler for Collector {
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.0.extend_from_slice(data);
+        Ok(data.len())
+    }
+}
+
+fn new_handle(url: &str, policy: &CrawlPolicy) -> Result<Easy2<Collector>, Error> {
+    let mut easy = Easy2::new(Collector(Vec::new()));
+    easy.url(url)?;
+    easy.useragent(&policy.user_agent)?;
+    easy.connect_timeout(policy.connect_timeout)?;
+    // curl has no read timeout as such: give up when less than a byte a
+    // second arrives for that long
+    easy.low_speed_limit(1)?;
+    easy.low_speed_time(policy.read_timeout)?;
+    Ok(easy)
+}
+
+fn body(easy: &mut Easy2<Collector>) -> Result<Vec<u8>, Error> {
+    let code = easy.response_code()?;
+    if code != 200 {
+        return Err(Error::HttpStatus(code));
+    }
+    Ok(std::mem::take(&mut easy.get_mut().0))
+}
+
+/// Downloads a file as is, retrying as the policy allows.
+fn get_bytes(url: &str, policy: &CrawlPolicy) -> Result<Vec<u8>, Error> {
+    let mut attempt = 0;
+    loop {
+        wait_turn(url, policy.min_interval);
+        let result = new_handle(url, policy).and_then(|easy| {
+            easy.perform()?;
+            Ok(easy)
+        });
+        match result.and_then(|mut easy| body(&mut easy)) {
+            Err(e) if policy.should_retry(&e, attempt) => {
+                std::thread::sleep(policy.backoff_for(attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Limits on how many pages a batch fetch downloads at once. How often it
+/// may hit the server is up to the [`CrawlPolicy`].
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    concurrency: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig { concurrency: 4 }
+    }
+}
+
+impl BatchConfig {
+    /// Maximum number of transfers in flight at once (at least 1).
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+/// A url waiting to be fetched.
+struct Pending {
+    index: usize,
+    attempt: u32,
+    not_before: Instant,
+}
+
+fn start(
+    multi: &Multi,
+    url: &str,
+    policy: &CrawlPolicy,
+    token: usize,
+) -> Result<Easy2Handle<Collector>, Error> {
+    let mut handle = multi.add2(new_handle(url, policy)?)?;
+    handle.set_token(token)?;
+    Ok(handle)
+}
+
+fn finish(
+    multi: &Multi,
+    handle: Easy2Handle<Collector>,
+    result: Result<(), curl::Error>,
+) -> Result<String, Error> {
+    let mut easy = multi.remove2(handle)?;
+    result?;
+    into_string(body(&mut easy)?)
+}
+
+/// Fetches every url concurrently, returning one result per url in the same
+/// order. Failed requests are retried as the policy allows, without holding
+/// up the rest of the batch.
+pub fn get_webpages(
+    urls: &[&str],
+    policy: &CrawlPolicy,
+    config: &BatchConfig,
+) -> Vec<Result<String, Error>> {
+    let mut results: Vec<Option<Result<String, Error>>> = urls.iter().map(|_| None).collect();
+    let now = Instant::now();
+    let mut pending: VecDeque<Pending> = (0..urls.len())
+        .map(|index| Pending {
+            index,
+            attempt: 0,
+            not_before: now,
+        })
+        .collect();
+    let mut active: HashMap<usize, (Easy2Handle<Collector>, u32)> = HashMap::new();
+    let multi = Multi::new();
+
+    while !pending.is_empty() || !active.is_empty() {
+        // start whatever the concurrency limit, backoff and policy allow
+        let now = Instant::now();
+        let mut waiting = VecDeque::new();
+        while let Some(next) = pending.pop_front() {
+            if active.len() >= config.concurrency
+                || next.not_before > now
+                || claim_turn(urls[next.index], policy.min_interval).is_err()
+            {
+                waiting.push_back(next);
+                continue;
+            }
+            match start(&multi, urls[next.index], policy, next.index) {
+                Ok(handle) => {
+                    active.insert(next.index, (handle, next.attempt));
+                }
+                Err(e) => results[next.index] = Some(Err(e)),
+            }
+        }
+        pending = waiting;
+
+        if let Err(e) = multi.perform() {
+            // the whole multi handle is broken, so fail everything left
+            let left = active
+                .keys()
+                .copied()
+                .chain(pending.iter().map(|p| p.index));
+            for i in left.collect::<Vec<_>>() {
+                results[i] = Some(Err(Error::from(e.clone())));
+            }
+            break;
+        }
+
+        let mut done = Vec::new();
+        multi.messages(|message| {
+            if let (Ok(token), Some(result)) = (message.token(), message.result()) {
+                done.push((token, result));
+            }
+        });
+        for (i, result) in done {
+            if let Some((handle, attempt)) = active.remove(&i) {
+                match finish(&multi, handle, result) {
+                    Err(e) if policy.should_retry(&e, attempt) => pending.push_back(Pending {
+                        index: i,
+                        attempt: attempt + 1,
+                        not_before: Instant::now() + policy.backoff_for(attempt),
+                    }),
+                    result => results[i] = Some(result),
+                }
+            }
+        }
+
+        if !active.is_empty() {
+            let _ = multi.wait(&mut [], Duration::from_millis(100));
+        } else if !pending.is_empty() {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|r| r.expect("every url gets a result"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_with_each_retry() {
+        let policy = CrawlPolicy::default();
+        let waits: Vec<Duration> = (0..3).map(|attempt| policy.backoff_for(attempt)).collect();
+        assert_eq!(waits, [2, 4, 8].map(Duration::from_secs));
+        let policy = policy.backoff(Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(4), Duration::from_millis(1600));
+    }
+
+    #[test]
+    fn only_server_errors_and_timeouts_are_retried() {
+        let policy = CrawlPolicy::default();
+        assert!(policy.should_retry(&Error::HttpStatus(503), 0));
+        assert!(policy.should_retry(&Error::HttpStatus(500), 2));
+        // curl's CURLE_OPERATION_TIMEDOUT
+        assert!(policy.should_retry(&Error::from(curl::Error::new(28)), 0));
+        assert!(!policy.should_retry(&Error::HttpStatus(404), 0));
+        assert!(!policy.should_retry(&Error::GameNotFound(1), 0));
+        // CURLE_COULDNT_CONNECT
+        assert!(!policy.should_retry(&Error::from(curl::Error::new(7)), 0));
+    }
+
+    #[test]
+    fn retries_stop_at_the_limit() {
+        let policy = CrawlPolicy::default();
+        assert!(!policy.should_retry(&Error::HttpStatus(503), 3));
+        let policy = policy.max_retries(0);
+        assert!(!policy.should_retry(&Error::HttpStatus(503), 0));
+    }
+}
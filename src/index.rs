@@ -0,0 +1,346 @@
+//! j-archive's listings of seasons and of the games in each season, for
+//! finding games without knowing their ids.
+//!
+//! `listseasons.php` lists every season with the dates it aired, and
+//! `showseason.php?season=<id>` lists the season's games with their show
+//! numbers and air dates. Both pages are kept in the [`Cache`] and fetched
+//! again once they are a day old, since the current season keeps growing.
+
+use crate::parse::{is_error_page, non_empty, text_of};
+use crate::{gen_season_url, gen_seasons_url, AirDate, Cache, Error};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::time::{Duration, SystemTime};
+
+/// How long a cached listing is trusted before it is fetched again.
+pub const LISTING_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+lazy_static! {
+    static ref RE_DATE: Regex = Regex::new(r"\d{4}-\d{2}-\d{2}").unwrap();
+    static ref RE_ARCHIVED: Regex = Regex::new(r"(\d+) games? archived").unwrap();
+    static ref RE_GAME: Regex = Regex::new(r"#(\d+), aired (\d{4}-\d{2}-\d{2})").unwrap();
+    static ref SEASON_LINK: Selector =
+        Selector::parse(r#"a[href*="showseason.php?season="]"#).unwrap();
+    static ref GAME_LINK: Selector =
+        Selector::parse(r#"a[href*="showgame.php?game_id="]"#).unwrap();
+    static ref TABLE_CELL: Selector = Selector::parse("td").unwrap();
+}
+
+/// A season as listed on `listseasons.php`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Season {
+    id: String,
+    name: String,
+    first_aired: Option<AirDate>,
+    last_aired: Option<AirDate>,
+    games_archived: Option<usize>,
+}
+
+impl Season {
+    /// j-archive's season identifier, as in [`GameInfo::season`](crate::GameInfo::season).
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    /// Display name, such as `Season 39` or `Super Jeopardy!`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn first_aired(&self) -> Option<AirDate> {
+        self.first_aired
+    }
+    pub fn last_aired(&self) -> Option<AirDate> {
+        self.last_aired
+    }
+    /// How many of the season's games j-archive has.
+    pub fn games_archived(&self) -> Option<usize> {
+        self.games_archived
+    }
+    /// Whether the season was on the air on `date`.
+    pub fn aired_on(&self, date: AirDate) -> bool {
+        match (self.first_aired, self.last_aired) {
+            (Some(first), Some(last)) => first <= date && date <= last,
+            _ => false,
+        }
+    }
+}
+
+/// One game as listed on a season's `showseason.php` page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameListing {
+    game_id: usize,
+    show_number: Option<usize>,
+    air_date: Option<AirDate>,
+    title: String,
+}
+
+impl GameListing {
+    pub fn game_id(&self) -> usize {
+        self.game_id
+    }
+    pub fn show_number(&self) -> Option<usize> {
+        self.show_number
+    }
+    pub fn air_date(&self) -> Option<AirDate> {
+        self.air_date
+    }
+    /// What j-archive says about the game, usually who played in it.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+}
+
+/// The row of the listing table an element is in.
+fn row_of(element: ElementRef) -> Option<ElementRef> {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "tr")
+}
+
+/// Text of a row, cell by cell, so numbers in neighbouring cells don't run
+/// together.
+fn cells_text(row: ElementRef) -> String {
+    let cells: Vec<String> = row.select(&TABLE_CELL).map(text_of).collect();
+    cells.join(" ")
+}
+
+fn parse_seasons(data: &str) -> Vec<Season> {
+    let html = Html::parse_document(data);
+    let mut seasons: Vec<Season> = Vec::new();
+    for link in html.select(&SEASON_LINK) {
+        let href = link.value().attr("href").unwrap_or("");
+        let id = href.split("season=").nth(1).unwrap_or("");
+        let id = id.split('&').next().unwrap_or("");
+        if id.is_empty() || seasons.iter().any(|season| season.id == id) {
+            continue;
+        }
+        let row = row_of(link).map(cells_text).unwrap_or_default();
+        let mut dates = RE_DATE
+            .find_iter(&row)
+            .filter_map(|m| m.as_str().parse().ok());
+        seasons.push(Season {
+            id: id.to_owned(),
+            name: non_empty(&text_of(link)).unwrap_or_else(|| id.to_owned()),
+            first_aired: dates.next(),
+            last_aired: dates.next(),
+            games_archived: RE_ARCHIVED
+                .captures(&row)
+                .and_then(|caps| caps[1].parse().ok()),
+        });
+    }
+    seasons
+}
+
+fn parse_season(data: &str) -> Vec<GameListing> {
+    let html = Html::parse_document(data);
+    let mut games: Vec<GameListing> = html
+        .select(&GAME_LINK)
+        .filter_map(|link| {
+            let href = link.value().attr("href")?;
+            let game_id = href.split("game_id=").nth(1)?.split('&').next()?;
+            let text = text_of(link);
+            let caps = RE_GAME.captures(&text);
+            // the title is the cell after the link's
+            let title = row_of(link)
+                .and_then(|row| row.select(&TABLE_CELL).nth(1))
+                .map(text_of)
+                .unwrap_or_default();
+            Some(GameListing {
+                game_id: game_id.parse().ok()?,
+                show_number: caps.as_ref().and_then(|caps| caps[1].parse().ok()),
+                air_date: caps.as_ref().and_then(|caps| caps[2].parse().ok()),
+                title,
+            })
+        })
+        .collect();
+    games.sort_by_key(|game| (game.air_date, game.show_number, game.game_id));
+    games.dedup_by_key(|game| game.game_id);
+    games
+}
+
+/// Cache name of the season list, or of one season's listing.
+pub(crate) fn listing_name(season: Option<&str>) -> String {
+    match season {
+        Some(season) => format!("season-{}", season),
+        None => "seasons".to_owned(),
+    }
+}
+
+/// What a listing page lists, from the cache while it is fresh, otherwise
+/// from the cache's fetcher. Only pages that list something are cached: if
+/// the fetch fails, or brings back an error page or an empty listing, a
+/// stale copy will do, and without one that is `not_found`.
+fn get_listing<T>(
+    name: &str,
+    url: &str,
+    cache: &Cache,
+    parse: fn(&str) -> Vec<T>,
+    not_found: Error,
+) -> Result<Vec<T>, Error> {
+    let cached = cache.read_listing(name)?;
+    let mut stale = None;
+    if let Some((data, fetched)) = &cached {
+        let age = SystemTime::now()
+            .duration_since(*fetched)
+            .unwrap_or_default();
+        let listed = parse(data);
+        if !listed.is_empty() {
+            if age < LISTING_MAX_AGE {
+                return Ok(listed);
+            }
+            stale = Some(listed);
+        }
+    }
+    let error = match cache.fetcher().fetch(url) {
+        Ok(data) if !is_error_page(&data) => {
+            let listed = parse(&data);
+            if !listed.is_empty() {
+                cache.write_listing(name, &data)?;
+                return Ok(listed);
+            }
+            not_found
+        }
+        Ok(_) => not_found,
+        Err(e) => e,
+    };
+    stale.ok_or(error)
+}
+
+/// Every season j-archive lists, newest first as the site orders them.
+pub fn get_seasons(cache: &Cache) -> Result<Vec<Season>, Error> {
+    let url = gen_seasons_url();
+    let not_found = Error::EmptyListing(url.clone());
+    get_listing(&listing_name(None), &url, cache, parse_seasons, not_found)
+}
+
+/// Finds the game with a show number. Show numbers only grow from season
+/// to season, so this binary searches the numbered seasons and fetches only
+/// a few of their listings. Seasons with no show numbers listed yet are
+/// left out of the search.
+pub fn find_game_by_show_number(show_number: usize, cache: &Cache) -> Result<GameListing, Error> {
+    let seasons = get_seasons(cache)?;
+    let mut numbered: Vec<(usize, &Season)> = seasons
+        .iter()
+        .filter_map(|season| Some((season.id.parse().ok()?, season)))
+        .collect();
+    numbered.sort_unstable_by_key(|&(number, _)| number);
+    let (mut low, mut high) = (0, numbered.len());
+    while low < high {
+        let middle = (low + high) / 2;
+        let games = match get_season_games(numbered[middle].1.id(), cache) {
+            Ok(games) => games,
+            // a season can be listed before any of its games are
+            Err(Error::SeasonNotFound(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let shows = || games.iter().filter_map(|game| game.show_number);
+        match (shows().min(), shows().max()) {
+            // nothing to compare with, so search the other seasons
+            (None, _) => {
+                numbered.remove(middle);
+                high -= 1;
+            }
+            (Some(first), _) if show_number < first => high = middle,
+            (_, Some(last)) if show_number > last => low = middle + 1,
+            _ => {
+                return games
+                    .into_iter()
+                    .find(|game| game.show_number == Some(show_number))
+                    .ok_or(Error::ShowNotFound(show_number));
+            }
+        }
+    }
+    Err(Error::ShowNotFound(show_number))
+}
+
+/// Finds the game that aired on a date, looking only at seasons that were
+/// on the air then. Regular seasons are searched before specials.
+pub fn find_game_by_air_date(air_date: AirDate, cache: &Cache) -> Result<GameListing, Error> {
+    let seasons = get_seasons(cache)?;
+    let mut candidates: Vec<&Season> = seasons
+        .iter()
+        .filter(|season| season.aired_on(air_date))
+        .collect();
+    candidates.sort_by_key(|season| season.id.parse::<usize>().is_err());
+    for season in candidates {
+        let games = match get_season_games(season.id(), cache) {
+            Ok(games) => games,
+            // a season can be listed before any of its games are
+            Err(Error::SeasonNotFound(_)) => continue,
+            Err(e) => return Err(e),
+        };
+        if let Some(game) = games
+            .into_iter()
+            .find(|game| game.air_date == Some(air_date))
+        {
+            return Ok(game);
+        }
+    }
+    Err(Error::AirDateNotFound(air_date))
+}
+
+/// The games of one season, oldest first.
+pub fn get_season_games(season: &str, cache: &Cache) -> Result<Vec<GameListing>, Error> {
+    get_listing(
+        &listing_name(Some(season)),
+        &gen_season_url(season),
+        cache,
+        parse_season,
+        Error::SeasonNotFound(season.to_owned()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Option<AirDate> {
+        s.parse().ok()
+    }
+
+    #[test]
+    fn seasons_keep_their_dates_and_game_counts() {
+        let seasons = parse_seasons(include_str!("../fixtures/seasons/seasons.html"));
+        let ids: Vec<&str> = seasons.iter().map(Season::id).collect();
+        assert_eq!(ids, ["39", "14", "superjeopardy"]);
+        assert_eq!(
+            seasons[1],
+            Season {
+                id: "14".to_owned(),
+                name: "Season 14".to_owned(),
+                first_aired: date("1997-09-01"),
+                last_aired: date("1998-07-24"),
+                games_archived: Some(2),
+            }
+        );
+        assert_eq!(seasons[2].name(), "Super Jeopardy!");
+        assert_eq!(seasons[2].games_archived(), Some(0));
+        assert!(seasons[1].aired_on(date("1997-09-15").unwrap()));
+        assert!(!seasons[1].aired_on(date("2023-03-03").unwrap()));
+    }
+
+    #[test]
+    fn season_games_are_oldest_first() {
+        let games = parse_season(include_str!("../fixtures/seasons/season-14.html"));
+        assert_eq!(
+            games,
+            [
+                GameListing {
+                    game_id: 3001,
+                    show_number: Some(3001),
+                    air_date: date("1997-09-15"),
+                    title: "Alice Example vs. Bob Placeholder vs. Carol Sample".to_owned(),
+                },
+                GameListing {
+                    game_id: 3002,
+                    show_number: Some(3002),
+                    air_date: date("1997-09-16"),
+                    title: "Alice Example vs. Frank Filler vs. Grace Dummy".to_owned(),
+                },
+            ]
+        );
+        let games = parse_season(include_str!("../fixtures/seasons/season-39.html"));
+        let ids: Vec<usize> = games.iter().map(GameListing::game_id).collect();
+        assert_eq!(ids, [8801, 8802]);
+    }
+}
@@ -0,0 +1,478 @@
+//! JSON form of a [`Game`](crate::Game), for sharing and hand-editing games.
+//!
+//! A game is a single object:
+//!
+//! ```json
+//! {
+//!   "schema_version": 5,
+//!   "info": {
+//!     "game_id": 3001,
+//!     "show_number": 3001,
+//!     "air_date": "1997-09-15",
+//!     "season": "14",
+//!     "comments": "Season 14 premiere."
+//!   },
+//!   "contestants": [
+//!     {
+//!       "player_id": 9001,
+//!       "name": "Alice Example",
+//!       "nickname": "Alice",
+//!       "occupation": "software engineer",
+//!       "hometown": "Columbus, Ohio"
+//!     },
+//!     "..."
+//!   ],
+//!   "rounds": [
+//!     {
+//!       "kind": "standard",
+//!       "categories": [
+//!         "WORLD CAPITALS",
+//!         {
+//!           "name": "WORDS WITH \"ANT\"",
+//!           "comment": "(Alex: Each response will be a word containing \"ant\".)"
+//!         },
+//!         "..."
+//!       ],
+//!       "board": [
+//!         [
+//!           {
+//!             "clue": "This city on the Seine...",
+//!             "answer": "Paris",
+//!             "value": 100,
+//!             "daily_double": false,
+//!             "order": 7,
+//!             "attempts": [{ "contestant": "Alice", "correct": true }]
+//!           },
+//!           {
+//!             "clue": "Captain Ahab hunts a white whale in this novel",
+//!             "answer": [{ "text": "Moby-Dick", "italic": true }],
+//!             "value": 100,
+//!             "daily_double": false,
+//!             "media": ["https://www.j-archive.com/media/1997-09-15_J_8.jpg"]
+//!           },
+//!           { "missing": "unrevealed" },
+//!           "..."
+//!         ],
+//!         "..."
+//!       ]
+//!     },
+//!     { "kind": "double", "categories": ["..."], "board": ["..."] },
+//!     {
+//!       "kind": "final",
+//!       "categories": ["AMERICAN AUTHORS"],
+//!       "board": [[{ "clue": "His pen name...", "answer": "Mark Twain" }]],
+//!       "responses": [
+//!         { "contestant": "Carol", "response": "Who is Melville?", "wager": 5300, "correct": false },
+//!         "..."
+//!       ]
+//!     }
+//!   ],
+//!   "scores": [
+//!     {
+//!       "time": "end_of_jeopardy",
+//!       "scores": [{ "contestant": "Carol", "score": 1100 }, "..."]
+//!     },
+//!     "..."
+//!   ]
+//! }
+//! ```
+//!
+//! - `rounds` are in the order they are played. `kind` is one of
+//!   `standard`, `double`, `final` and `tiebreaker`.
+//! - A category is its name, or an object with `name` and `comment` when
+//!   j-archive has the host's explanation of the category.
+//! - A round's board is a list of rows, top row first, and every row has one
+//!   clue per category, left to right. Usually that is 6 categories and 5
+//!   rows; `final` and `tiebreaker` rounds have one category and one clue.
+//! - A spot with no clue to play is `{ "missing": reason }`, where the
+//!   reason is `unrevealed` (never picked), `not_archived` (played, but
+//!   j-archive has no text for it) or `media_only` (only a picture, sound
+//!   or video, with no correct response). A media-only clue that has a
+//!   response is a clue, with its `media`.
+//! - `value` is the printed board value.
+//! - `wager` appears only on daily doubles and is what the original
+//!   contestant bet.
+//! - `order` is when the clue was picked in the original game, counting
+//!   from 1 in each round.
+//! - `media` lists links to the pictures, sounds and videos shown with a
+//!   clue.
+//! - `attempts` lists who rang in, in order, by contestant nickname.
+//!   `triple_stumper` is `true` when nobody gave the correct response.
+//! - `scores` are the broadcast's score tables. `time` is one of
+//!   `first_break`, `end_of_jeopardy`, `end_of_double_jeopardy` and `final`,
+//!   and a score may carry a `remark` such as `"2nd place: $2,000"`.
+//! - `clue` and `answer` are strings, or lists of spans when part of the
+//!   text is styled. A span has `text` and any of `italic`, `underline` and
+//!   `bold` set to `true`.
+//! - Every field of `info` and of a contestant, `contestants`, `scores`,
+//!   a final round's `responses`, and
+//!   `value`/`daily_double`/`wager`/`order`/`attempts`/`triple_stumper`/`media`
+//!   on clues may be left out.
+//!
+//! `schema_version` is bumped whenever a change would make older readers
+//! misread a file. Files with a newer version than [`JSON_SCHEMA_VERSION`]
+//! are rejected. Older files still load: version 1 only had string clues,
+//! versions 1 and 2 had fixed `jeopardy`, `double_jeopardy` and
+//! `final_jeopardy` fields in place of `rounds`, before version 4 an
+//! unrevealed clue was one with empty text, and before version 5 categories
+//! were always plain names. `info.special` is read as `info.comments`.
+
+use crate::{
+    AirDate, Category, ClueSlot, Error, Game, JeopardyQuestion, MissingReason, RichText, Round,
+    StyledSpan, PARSER_VERSION,
+};
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Version of the JSON game schema written by [`Game::to_json`].
+pub const JSON_SCHEMA_VERSION: u32 = 5;
+
+#[derive(Serialize)]
+struct VersionedRef<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    game: &'a Game,
+}
+
+/// A parsed game in the cache, stamped with the parser that produced it.
+#[derive(Serialize)]
+struct ParsedRef<'a> {
+    parser_version: u32,
+    #[serde(flatten)]
+    game: VersionedRef<'a>,
+}
+
+#[derive(Deserialize)]
+struct Parsed {
+    parser_version: u32,
+    #[serde(flatten)]
+    game: serde_json::Value,
+}
+
+impl Serialize for AirDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AirDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<AirDate, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| de::Error::custom(format!("invalid air date `{}`", s)))
+    }
+}
+
+/// Unstyled text is written as a plain string to keep files easy to edit.
+impl Serialize for RichText {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_styled() {
+            self.spans().serialize(serializer)
+        } else {
+            serializer.serialize_str(self.as_str())
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RichTextRepr {
+    Plain(String),
+    Spans(Vec<StyledSpan>),
+}
+
+impl<'de> Deserialize<'de> for RichText {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<RichText, D::Error> {
+        Ok(match RichTextRepr::deserialize(deserializer)? {
+            RichTextRepr::Plain(text) => RichText::from(&text[..]),
+            RichTextRepr::Spans(spans) => RichText::from(spans),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct CategoryRef<'a> {
+    name: &'a str,
+    comment: &'a str,
+}
+
+/// Like rich text, a category without a comment is just its name.
+impl Serialize for Category {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.comment() {
+            Some(comment) => CategoryRef {
+                name: self.name(),
+                comment,
+            }
+            .serialize(serializer),
+            None => serializer.serialize_str(self.name()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CategoryRepr {
+    Name(String),
+    Commented {
+        name: String,
+        #[serde(default)]
+        comment: Option<String>,
+    },
+}
+
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Category, D::Error> {
+        Ok(match CategoryRepr::deserialize(deserializer)? {
+            CategoryRepr::Name(name) => Category::new(&name, None),
+            CategoryRepr::Commented { name, comment } => Category::new(&name, comment.as_deref()),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct MissingRef {
+    missing: MissingReason,
+}
+
+impl Serialize for ClueSlot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ClueSlot::Clue(question) => question.serialize(serializer),
+            ClueSlot::Missing(reason) => MissingRef { missing: *reason }.serialize(serializer),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ClueSlotRepr {
+    Missing { missing: MissingReason },
+    Clue(Box<JeopardyQuestion>),
+}
+
+impl<'de> Deserialize<'de> for ClueSlot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ClueSlot, D::Error> {
+        Ok(match ClueSlotRepr::deserialize(deserializer)? {
+            ClueSlotRepr::Missing { missing } => ClueSlot::Missing(missing),
+            ClueSlotRepr::Clue(question) => ClueSlot::Clue(question),
+        })
+    }
+}
+
+fn check_round(index: usize, round: &mut Round) -> serde_json::Result<()> {
+    let columns = round.categories.len();
+    if columns == 0 || round.board.is_empty() || round.board.iter().any(|row| row.len() != columns)
+    {
+        return Err(de::Error::custom(format!(
+            "round {} must have at least one category and one clue per category in every row",
+            index + 1
+        )));
+    }
+    if round.kind.is_single_clue() && (columns != 1 || round.board.len() != 1) {
+        return Err(de::Error::custom(format!(
+            "round {} ({:?}) must have one category and one clue",
+            index + 1,
+            round.kind
+        )));
+    }
+    for (y, row) in round.board.iter_mut().enumerate() {
+        for (x, slot) in row.iter_mut().enumerate() {
+            if let ClueSlot::Clue(question) = slot {
+                question.x = x;
+                question.y = y;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites a version 1 or 2 game, which had fixed `jeopardy`,
+/// `double_jeopardy` and `final_jeopardy` fields, into a list of rounds.
+fn upgrade_rounds(game: &mut Map<String, Value>) {
+    let mut rounds = Vec::new();
+    for &(key, kind) in &[("jeopardy", "standard"), ("double_jeopardy", "double")] {
+        if let Some(Value::Object(mut round)) = game.remove(key) {
+            round.insert("kind".to_owned(), kind.into());
+            rounds.push(Value::Object(round));
+        }
+    }
+    if let Some(Value::Object(mut round)) = game.remove("final_jeopardy") {
+        let category = round.remove("category").unwrap_or_else(|| "".into());
+        let clue = round.remove("clue").unwrap_or_default();
+        round.insert("kind".to_owned(), "final".into());
+        round.insert("categories".to_owned(), Value::Array(vec![category]));
+        round.insert("board".to_owned(), vec![vec![clue]].into());
+        rounds.push(Value::Object(round));
+    }
+    game.insert("rounds".to_owned(), Value::Array(rounds));
+}
+
+/// Before version 4, unrevealed clues were written as clues with empty
+/// text. Marks them as missing instead.
+fn upgrade_missing(game: &mut Map<String, Value>) {
+    let rounds = match game.get_mut("rounds") {
+        Some(Value::Array(rounds)) => rounds,
+        _ => return,
+    };
+    let slots = rounds
+        .iter_mut()
+        .filter_map(|round| round.get_mut("board"))
+        .filter_map(Value::as_array_mut)
+        .flatten()
+        .filter_map(Value::as_array_mut)
+        .flatten();
+    for slot in slots {
+        let empty = match slot.get("clue") {
+            Some(Value::String(clue)) => clue.is_empty(),
+            Some(Value::Array(spans)) => spans.is_empty(),
+            _ => false,
+        };
+        if empty {
+            *slot = serde_json::json!({ "missing": MissingReason::Unrevealed });
+        }
+    }
+}
+
+pub(crate) fn to_parsed_cache(game: &Game) -> String {
+    let parsed = ParsedRef {
+        parser_version: PARSER_VERSION,
+        game: VersionedRef {
+            schema_version: JSON_SCHEMA_VERSION,
+            game,
+        },
+    };
+    serde_json::to_string(&parsed).expect("game is always serializable")
+}
+
+/// Reads a cached parsed game, or `None` if it is unreadable or was made by
+/// a different parser version and should be rebuilt.
+pub(crate) fn from_parsed_cache(data: &str) -> Option<Game> {
+    let parsed: Parsed = serde_json::from_str(data).ok()?;
+    if parsed.parser_version != PARSER_VERSION {
+        return None;
+    }
+    Game::from_json(&parsed.game.to_string()).ok()
+}
+
+impl Game {
+    /// Serializes the game in the format described in the [module docs](self).
+    pub fn to_json(&self) -> String {
+        let versioned = VersionedRef {
+            schema_version: JSON_SCHEMA_VERSION,
+            game: self,
+        };
+        serde_json::to_string_pretty(&versioned).expect("game is always serializable")
+    }
+
+    pub fn from_json(data: &str) -> Result<Game, Error> {
+        let mut value: Value = serde_json::from_str(data)?;
+        let schema_version = value
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| Error::Json(de::Error::missing_field("schema_version")))?;
+        if schema_version > JSON_SCHEMA_VERSION as u64 {
+            return Err(Error::Json(de::Error::custom(format!(
+                "unsupported schema version {} (expected at most {})",
+                schema_version, JSON_SCHEMA_VERSION
+            ))));
+        }
+        if schema_version < 3 {
+            if let Value::Object(game) = &mut value {
+                upgrade_rounds(game);
+            }
+        }
+        if schema_version < 4 {
+            if let Value::Object(game) = &mut value {
+                upgrade_missing(game);
+            }
+        }
+        // `schema_version` is left over and ignored like any unknown field
+        let mut game: Game = serde_json::from_value(value)?;
+        if game.rounds.is_empty() {
+            return Err(Error::Json(de::Error::custom("game has no rounds")));
+        }
+        for (index, round) in game.rounds.iter_mut().enumerate() {
+            check_round(index, round)?;
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_game, RoundKind};
+
+    /// A version 2 game: fixed round fields and an empty clue for a spot
+    /// that was never revealed.
+    const VERSION_2: &str = r#"{
+        "schema_version": 2,
+        "info": { "game_id": 1, "show_number": 1, "air_date": "1984-09-10", "season": "1" },
+        "jeopardy": {
+            "categories": ["LAKES & RIVERS"],
+            "board": [
+                [{ "clue": "River mentioned most often in the Bible", "answer": "the Jordan", "value": 100, "daily_double": false }],
+                [{ "clue": "", "answer": "", "value": 200, "daily_double": false }]
+            ]
+        },
+        "final_jeopardy": {
+            "category": "HOLIDAYS",
+            "clue": { "clue": "The third Monday of January", "answer": "Martin Luther King Day", "value": 0, "daily_double": false }
+        }
+    }"#;
+
+    fn answer(round: &Round, x: usize, y: usize) -> Option<&str> {
+        round.board()[y][x].clue().map(|q| q.answer())
+    }
+
+    #[test]
+    fn parsed_games_round_trip() {
+        let game = parse_game(3001, include_str!("../fixtures/games/3001.html")).unwrap();
+        let json = game.to_json();
+        assert_eq!(Game::from_json(&json).unwrap().to_json(), json);
+    }
+
+    #[test]
+    fn fixed_round_fields_become_rounds() {
+        let game = Game::from_json(VERSION_2).unwrap();
+        let kinds: Vec<RoundKind> = game.rounds().iter().map(|round| round.kind()).collect();
+        assert_eq!(kinds, [RoundKind::Standard, RoundKind::Final]);
+        assert_eq!(answer(&game.rounds()[0], 0, 0), Some("the Jordan"));
+        let final_jeopardy = game.final_jeopardy().unwrap();
+        assert_eq!(final_jeopardy.categories()[0].name(), "HOLIDAYS");
+        assert_eq!(answer(final_jeopardy, 0, 0), Some("Martin Luther King Day"));
+    }
+
+    #[test]
+    fn empty_clues_become_unrevealed() {
+        let mut version_3: Value =
+            serde_json::from_str(&Game::from_json(VERSION_2).unwrap().to_json()).unwrap();
+        version_3["schema_version"] = 3.into();
+        version_3["rounds"][0]["board"][1][0] = serde_json::json!({
+            "clue": "", "answer": "", "value": 200, "daily_double": false
+        });
+        for data in [version_3.to_string().as_str(), VERSION_2] {
+            let game = Game::from_json(data).unwrap();
+            let jeopardy = &game.rounds()[0];
+            assert_eq!(answer(jeopardy, 0, 0), Some("the Jordan"));
+            assert_eq!(
+                jeopardy.board()[1][0].missing(),
+                Some(MissingReason::Unrevealed)
+            );
+        }
+    }
+
+    #[test]
+    fn newer_schemas_are_rejected() {
+        let game = parse_game(3001, include_str!("../fixtures/games/3001.html")).unwrap();
+        let newer = game.to_json().replacen(
+            &format!("\"schema_version\": {}", JSON_SCHEMA_VERSION),
+            &format!("\"schema_version\": {}", JSON_SCHEMA_VERSION + 1),
+            1,
+        );
+        assert!(matches!(Game::from_json(&newer), Err(Error::Json(_))));
+    }
+}
@@ -1,223 +1,767 @@
-use curl::easy::Easy;
-use regex::Regex;
-use std::collections::hash_map::DefaultHasher;
-use std::fs::{self, File};
-use std::hash::{Hash, Hasher};
-use std::io;
-use std::io::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
 
 #[macro_use]
 extern crate lazy_static;
 
-lazy_static! {
-    static ref RE_HTML: Regex =
-        Regex::new(r#"(?:<(i|u|b)>|</(i|u|b)>|\&lt;(i|u|b)\&gt;|\&lt;/(i|u|b)\&gt;)"#).unwrap();
-    static ref RE_CATEGORY: Regex = Regex::new(r#"<td class="category_name">(.+)</td>"#).unwrap();
-    static ref RE_CLUE: Regex =
-        Regex::new(r#"id="clue_J_(\d)_(\d)" class="clue_text">(.+)</td>"#).unwrap();
-    static ref RE_ANSWER: Regex =
-        Regex::new(r#"clue_J_(\d)_(\d).+correct_response\&quot;\&gt;(.+)\&lt;/em"#).unwrap();
-    static ref RE_CLUE_D: Regex =
-        Regex::new(r#"id="clue_DJ_(\d)_(\d)" class="clue_text">(.+)</td>"#).unwrap();
-    static ref RE_ANSWER_D: Regex =
-        Regex::new(r#"clue_DJ_(\d)_(\d).+correct_response\&quot;\&gt;(.+)\&lt;/em"#).unwrap();
-    static ref RE_FINAL_CLUE: Regex =
-        Regex::new(r#"id="clue_FJ" class="clue_text">(.+)</td>"#).unwrap();
-    static ref RE_FINAL_ANSWER: Regex =
-        Regex::new(r#"quot;correct_response\\&quot;\&gt;(.+)\&lt;/em"#).unwrap();
-}
+mod cache;
+mod error;
+mod fetch;
+mod index;
+mod json;
+mod offline;
+mod parse;
+mod text;
 
-fn gen_url(game_id: usize) -> String {
-    let base_url = "https://www.j-archive.com/showgame.php";
-    format!("{}?game_id={}", base_url, game_id.to_string())
-}
+pub use cache::{Cache, CACHE_DIR_VAR, FETCHER_VAR};
+pub use error::Error;
+pub use fetch::{get_webpages, BatchConfig, CrawlPolicy, CurlFetcher, Fetcher};
+pub use index::{
+    find_game_by_air_date, find_game_by_show_number, get_season_games, get_seasons, GameListing,
+    Season, LISTING_MAX_AGE,
+};
+pub use json::JSON_SCHEMA_VERSION;
+pub use offline::{CacheFetcher, FixtureFetcher};
+use parse::is_error_page;
+pub use parse::parse_game;
+use std::path::PathBuf;
+pub use text::{RichText, StyledSpan, TextStyle};
 
-fn get_webpage(url: &String) -> String {
-    let mut handle = Easy::new();
-    handle.url(&url).unwrap();
+/// Version of the page parser. Bump it whenever [`parse_game`] would produce
+/// a different `Game` from the same page, so cached games get rebuilt from
+/// their cached html.
+pub const PARSER_VERSION: u32 = 12;
 
-    let mut buf = Vec::new();
+/// j-archive itself.
+pub const DEFAULT_BASE_URL: &str = "https://www.j-archive.com";
 
-    {
-        let mut transfer = handle.transfer();
-        transfer
-            .write_function(|new_data| {
-                buf.extend_from_slice(new_data);
-                Ok(new_data.len())
-            })
-            .unwrap();
-        transfer.perform().unwrap();
-    }
+/// Environment variable that points the scraper at another server with
+/// j-archive's urls, such as the `standin` binary.
+pub const BASE_URL_VAR: &str = "BRRR_BASE_URL";
 
-    let s = match std::str::from_utf8(buf.as_slice()) {
-        Ok(v) => v,
-        Err(e) => panic!("Invalid UTF-8 sequence: {}", e),
-    };
+/// Where pages are fetched from: `$BRRR_BASE_URL` if set, otherwise
+/// j-archive. Never ends in a slash.
+pub fn base_url() -> String {
+    match std::env::var(BASE_URL_VAR) {
+        Ok(url) if !url.is_empty() => url.trim_end_matches('/').to_owned(),
+        _ => DEFAULT_BASE_URL.to_owned(),
+    }
+}
 
-    return s.to_owned();
+/// Moves a j-archive url, such as a clue's media link, onto [`base_url`].
+/// Other urls are left alone.
+fn rebase_url(url: &str) -> String {
+    let origins = [
+        "https://www.j-archive.com/",
+        "http://www.j-archive.com/",
+        "https://j-archive.com/",
+        "http://j-archive.com/",
+    ];
+    match origins.iter().find_map(|origin| url.strip_prefix(origin)) {
+        Some(path) => format!("{}/{}", base_url(), path),
+        None => url.to_owned(),
+    }
 }
 
-fn get_webpages(urls: Vec<&String>) -> Vec<String> {
-    return vec![String::new()];
+fn gen_url(game_id: usize) -> String {
+    format!("{}/showgame.php?game_id={}", base_url(), game_id)
 }
 
-fn cache_read(url: &String) -> Option<String> {
-    let filename = "./cache/".to_owned() + &url[47..];
-    let data = fs::read_to_string(filename);
-    match data {
-        Ok(x) => Some(x),
-        Err(e) => None,
-    }
+fn gen_seasons_url() -> String {
+    format!("{}/listseasons.php", base_url())
 }
 
-fn cache_write(url: &String, data: &String) {
-    fs::create_dir_all("./cache");
-    let filename = "./cache/".to_owned() + &url[47..];
-    fs::write(filename, data).expect("Unable to write file");
+fn gen_season_url(season: &str) -> String {
+    format!("{}/showseason.php?season={}", base_url(), season)
 }
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct JeopardyQuestion {
-    clue: String,
-    answer: String,
+    clue: RichText,
+    answer: RichText,
+    #[serde(skip)]
     x: usize, // num category from left to right
+    #[serde(skip)]
     y: usize, // num question going down
-    value: usize,
+    #[serde(default)]
+    value: usize, // printed board value, even for daily doubles
+    #[serde(default)]
+    daily_double: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wager: Option<usize>, // what the original contestant wagered on a daily double
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order: Option<usize>, // when the clue was picked in the original game, from 1
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attempts: Vec<Attempt>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    triple_stumper: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    media: Vec<String>, // urls of pictures, sounds and videos shown with the clue
 }
 
 impl JeopardyQuestion {
-    fn default() -> JeopardyQuestion {
-        JeopardyQuestion {
-            clue: String::new(),
-            answer: String::new(),
-            x: 0,
-            y: 0,
-            value: 0,
-        }
-    }
     pub fn value(&self) -> usize {
-        return self.value;
+        self.value
+    }
+    pub fn is_daily_double(&self) -> bool {
+        self.daily_double
+    }
+    pub fn wager(&self) -> Option<usize> {
+        self.wager
     }
     pub fn clue(&self) -> &str {
-        return &self.clue[..];
+        self.clue.as_str()
     }
     pub fn answer(&self) -> &str {
-        return &self.answer[..];
-    }
-}
-
-fn clean_html(data: String) -> String {
-    let data = RE_HTML.replace_all(&data[..], "");
-    let data = data.replace("&amp;", "&");
-    let data = data.replace("<br />", "");
-    let data = data.replace("\\'", "'");
-    let data = data.replace("\\\"", "\"");
-    return data;
-}
-
-fn populate_board(data: &String, board: &mut Board, double: bool) {
-    // parse clues
-    let re_clue: &Regex = if !double { &RE_CLUE } else { &RE_CLUE_D };
-    for caps in re_clue.captures_iter(&data[..]) {
-        let x: usize = caps.get(1).unwrap().as_str().parse().unwrap();
-        let y: usize = caps.get(2).unwrap().as_str().parse().unwrap();
-        let x = x - 1;
-        let y = y - 1;
-        let clue = caps.get(3).unwrap().as_str().to_string();
-        let clue = clean_html(clue);
-        board[y][x].x = x;
-        board[y][x].y = y;
-        board[y][x].clue.push_str(&clue);
-        board[y][x].value = (y + 1) * 200;
-        if double {
-            board[y][x].value *= 2;
-        }
+        self.answer.as_str()
     }
-
-    // parse answers
-    let re_answer: &Regex = if !double { &RE_ANSWER } else { &RE_ANSWER_D };
-    for caps in re_answer.captures_iter(&data[..]) {
-        let x: usize = caps.get(1).unwrap().as_str().parse().unwrap();
-        let y: usize = caps.get(2).unwrap().as_str().parse().unwrap();
-        let x = x - 1;
-        let y = y - 1;
-        let answer = caps.get(3).unwrap().as_str().to_string();
-        let answer = clean_html(answer);
-        board[y][x].answer.push_str(&answer);
+    pub fn rich_clue(&self) -> &RichText {
+        &self.clue
+    }
+    pub fn rich_answer(&self) -> &RichText {
+        &self.answer
+    }
+    /// Position of the clue in the original game's pick order, starting at 1.
+    pub fn order(&self) -> Option<usize> {
+        self.order
     }
+    /// Everyone who rang in, in order.
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+    /// Whether no contestant gave the correct response.
+    pub fn is_triple_stumper(&self) -> bool {
+        self.triple_stumper
+    }
+    /// Links to the pictures, sounds and videos that went with the clue.
+    /// See [`get_media`] to download them.
+    pub fn media(&self) -> &[String] {
+        &self.media
+    }
+}
+
+/// One contestant ringing in on a clue.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attempt {
+    contestant: String, // nickname, see `Contestant::nickname`
+    correct: bool,
 }
 
-fn populate_categories(data: &String, categories: &mut Vec<Category>) {
-    for caps in RE_CATEGORY.captures_iter(&data[..]) {
-        let category = caps.get(1).unwrap().as_str().to_string();
-        let category = clean_html(category);
-        categories.push(category);
+impl Attempt {
+    pub fn contestant(&self) -> &str {
+        &self.contestant
     }
+    pub fn is_correct(&self) -> bool {
+        self.correct
+    }
+}
+
+/// Why a spot on the board has no clue to play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingReason {
+    /// Nobody picked the clue before time ran out.
+    Unrevealed,
+    /// The clue was played, but j-archive has no record of its text.
+    NotArchived,
+    /// The clue was only a picture, sound or video, and j-archive has no
+    /// correct response for it. Media-only clues with a response are kept
+    /// as clues.
+    MediaOnly,
 }
 
-fn populate_final_jeopardy(data: &String, final_jeopardy: &mut JeopardyQuestion) {
-    for caps in RE_FINAL_CLUE.captures_iter(&data) {
-        let clue = caps.get(1).unwrap().as_str();
-        let clue = clean_html(clue.to_string());
-        final_jeopardy.clue = clue;
+/// One spot on the board: a clue, or the reason there isn't one.
+#[derive(Clone, Debug)]
+pub enum ClueSlot {
+    Clue(Box<JeopardyQuestion>),
+    Missing(MissingReason),
+}
+
+impl ClueSlot {
+    pub fn clue(&self) -> Option<&JeopardyQuestion> {
+        match self {
+            ClueSlot::Clue(question) => Some(question.as_ref()),
+            ClueSlot::Missing(_) => None,
+        }
     }
-    for caps in RE_FINAL_ANSWER.captures_iter(&data) {
-        let answer = caps.get(1).unwrap().as_str();
-        let answer = clean_html(answer.to_string());
-        final_jeopardy.answer = answer;
+    pub fn missing(&self) -> Option<MissingReason> {
+        match self {
+            ClueSlot::Clue(_) => None,
+            ClueSlot::Missing(reason) => Some(*reason),
+        }
+    }
+    pub fn is_missing(&self) -> bool {
+        self.missing().is_some()
     }
 }
 
 pub fn print_board(board: &Board) {
     for row in board {
-        for jq in row {
-            println!("{:?}", jq);
+        for slot in row {
+            match slot {
+                ClueSlot::Clue(jq) => println!("{:?}", jq),
+                ClueSlot::Missing(reason) => println!("{:?}", reason),
+            }
+        }
+    }
+}
+
+/// A category title, with the host's explanation of it if j-archive has
+/// one, e.g. `(Ken: Each response will be a word containing "ant".)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Category {
+    name: String,
+    comment: Option<String>,
+}
+
+impl Category {
+    pub fn new(name: &str, comment: Option<&str>) -> Category {
+        Category {
+            name: name.to_owned(),
+            comment: comment.map(str::to_owned),
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+pub type Board = Vec<Vec<ClueSlot>>;
+
+/// Date an episode was broadcast, as printed by j-archive (`YYYY-MM-DD`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AirDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl AirDate {
+    pub fn new(year: u16, month: u8, day: u8) -> Option<AirDate> {
+        if (1..=12).contains(&month) && (1..=31).contains(&day) {
+            Some(AirDate { year, month, day })
+        } else {
+            None
+        }
+    }
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for AirDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for AirDate {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<AirDate, ()> {
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or(());
+        let year = next()?.parse().map_err(|_| ())?;
+        let month = next()?.parse().map_err(|_| ())?;
+        let day = next()?.parse().map_err(|_| ())?;
+        AirDate::new(year, month, day).ok_or(())
+    }
+}
+
+/// The ways people name a game: j-archive's game id (`1234`), the show
+/// number (`#4500`) or the day it aired (`2004-03-03`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameKey {
+    GameId(usize),
+    ShowNumber(usize),
+    AirDate(AirDate),
+}
+
+impl FromStr for GameKey {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<GameKey, ()> {
+        let s = s.trim();
+        if let Some(show_number) = s.strip_prefix('#') {
+            return show_number.parse().map(GameKey::ShowNumber).map_err(|_| ());
+        }
+        if let Ok(game_id) = s.parse() {
+            return Ok(GameKey::GameId(game_id));
+        }
+        s.parse().map(GameKey::AirDate)
+    }
+}
+
+/// Episode metadata scraped from the game page.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameInfo {
+    game_id: usize,
+    show_number: Option<usize>,
+    air_date: Option<AirDate>,
+    season: Option<String>,
+    // older files called these `special`
+    #[serde(alias = "special")]
+    comments: Option<String>,
+}
+
+impl GameInfo {
+    pub fn game_id(&self) -> usize {
+        self.game_id
+    }
+    pub fn show_number(&self) -> Option<usize> {
+        self.show_number
+    }
+    pub fn air_date(&self) -> Option<AirDate> {
+        self.air_date
+    }
+    /// j-archive season identifier, usually a number but sometimes a name
+    /// such as `superjeopardy`.
+    pub fn season(&self) -> Option<&str> {
+        self.season.as_deref()
+    }
+    /// j-archive's free-text notes on the game, such as `Season 14
+    /// premiere.` or the name of a tournament.
+    pub fn comments(&self) -> Option<&str> {
+        self.comments.as_deref()
+    }
+}
+
+/// A player on the episode, as introduced at the top of the game page.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Contestant {
+    player_id: Option<usize>,
+    name: String,
+    nickname: String,
+    occupation: Option<String>,
+    hometown: Option<String>,
+}
+
+impl Contestant {
+    /// Id of the contestant's j-archive player page.
+    pub fn player_id(&self) -> Option<usize> {
+        self.player_id
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Short name used for the contestant in attempts and scores.
+    pub fn nickname(&self) -> &str {
+        &self.nickname
+    }
+    pub fn occupation(&self) -> Option<&str> {
+        self.occupation.as_deref()
+    }
+    pub fn hometown(&self) -> Option<&str> {
+        self.hometown.as_deref()
+    }
+}
+
+/// What part of the show a round is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundKind {
+    /// The Jeopardy round.
+    Standard,
+    /// Double Jeopardy, with doubled values.
+    Double,
+    /// Final Jeopardy: one clue, secret wagers.
+    Final,
+    /// A single clue played only to break a tie after Final Jeopardy.
+    Tiebreaker,
+}
+
+impl RoundKind {
+    /// Whether the round is one clue rather than a board.
+    pub fn is_single_clue(&self) -> bool {
+        matches!(self, RoundKind::Final | RoundKind::Tiebreaker)
+    }
+}
+
+/// One round of play: categories across the top and a board of clues under
+/// them, one column per category. Final Jeopardy and tiebreakers are a
+/// single category with a single clue.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Round {
+    kind: RoundKind,
+    categories: Vec<Category>,
+    board: Board,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    responses: Vec<FinalResponse>,
+}
+
+impl Round {
+    fn new(kind: RoundKind, columns: usize, rows: usize) -> Round {
+        Round {
+            kind,
+            categories: Vec::new(),
+            // spots the page has no cell for are filled in by the parser
+            board: vec![vec![ClueSlot::Missing(MissingReason::NotArchived); columns]; rows],
+            responses: Vec::new(),
         }
     }
+    pub fn kind(&self) -> RoundKind {
+        self.kind
+    }
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+    pub fn columns(&self) -> usize {
+        self.categories.len()
+    }
+    pub fn rows(&self) -> usize {
+        self.board.len()
+    }
+    /// The category and clue of a single-clue round.
+    pub fn single_clue(&self) -> Option<(&Category, &JeopardyQuestion)> {
+        match (&self.categories[..], &self.board[..]) {
+            ([category], [row]) if row.len() == 1 => Some((category, row[0].clue()?)),
+            _ => None,
+        }
+    }
+    /// Column and row of each clue in the order the original contestants
+    /// picked them. Clues with no recorded order are left out.
+    pub fn pick_order(&self) -> Vec<(usize, usize)> {
+        let mut picks: Vec<(usize, usize, usize)> = self
+            .board
+            .iter()
+            .flatten()
+            .filter_map(ClueSlot::clue)
+            .filter_map(|q| q.order.map(|order| (order, q.x, q.y)))
+            .collect();
+        picks.sort_unstable();
+        picks.into_iter().map(|(_, x, y)| (x, y)).collect()
+    }
+    /// How many spots on the board have no clue for the given reason.
+    pub fn count_missing(&self, reason: MissingReason) -> usize {
+        self.board
+            .iter()
+            .flatten()
+            .filter(|slot| slot.missing() == Some(reason))
+            .count()
+    }
+    /// In Final Jeopardy, what each contestant wrote and wagered, in the
+    /// order j-archive lists them.
+    pub fn responses(&self) -> &[FinalResponse] {
+        &self.responses
+    }
 }
 
-pub type Category = String;
-pub type Board = Vec<Vec<JeopardyQuestion>>;
-pub type Game = (Vec<Category>, Board, Board, JeopardyQuestion);
+/// One contestant's Final Jeopardy response and wager.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalResponse {
+    contestant: String, // nickname
+    response: String,
+    wager: Option<usize>,
+    correct: bool,
+}
 
-pub fn get_game_data(game_id: usize) -> Option<Game> {
+impl FinalResponse {
+    pub fn contestant(&self) -> &str {
+        &self.contestant
+    }
+    pub fn response(&self) -> &str {
+        &self.response
+    }
+    pub fn wager(&self) -> Option<usize> {
+        self.wager
+    }
+    pub fn is_correct(&self) -> bool {
+        self.correct
+    }
+}
+
+/// Points in the game where j-archive records everyone's score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreTime {
+    /// The first commercial break, partway through the Jeopardy round.
+    FirstBreak,
+    EndOfJeopardy,
+    EndOfDoubleJeopardy,
+    Final,
+}
+
+/// Everyone's score at one [`ScoreTime`], as shown on the broadcast.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scores {
+    time: ScoreTime,
+    scores: Vec<Score>,
+}
+
+impl Scores {
+    pub fn time(&self) -> ScoreTime {
+        self.time
+    }
+    pub fn scores(&self) -> &[Score] {
+        &self.scores
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    contestant: String, // nickname
+    score: isize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    remark: Option<String>, // e.g. "2nd place: $2,000"
+}
+
+impl Score {
+    pub fn contestant(&self) -> &str {
+        &self.contestant
+    }
+    pub fn score(&self) -> isize {
+        self.score
+    }
+    pub fn remark(&self) -> Option<&str> {
+        self.remark.as_deref()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Game {
+    info: GameInfo,
+    #[serde(default)]
+    contestants: Vec<Contestant>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scores: Vec<Scores>,
+    rounds: Vec<Round>,
+}
+
+impl Game {
+    pub fn info(&self) -> &GameInfo {
+        &self.info
+    }
+    pub fn contestants(&self) -> &[Contestant] {
+        &self.contestants
+    }
+    /// Looks up a contestant by the nickname used in [`Attempt`]s.
+    pub fn contestant(&self, nickname: &str) -> Option<&Contestant> {
+        self.contestants.iter().find(|c| c.nickname == nickname)
+    }
+    /// Score tables from the broadcast, in the order they were taken.
+    pub fn scores(&self) -> &[Scores] {
+        &self.scores
+    }
+    pub fn scores_at(&self, time: ScoreTime) -> Option<&Scores> {
+        self.scores.iter().find(|scores| scores.time == time)
+    }
+    /// Every round in the order it is played.
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+    /// The first round of the given kind.
+    pub fn round(&self, kind: RoundKind) -> Option<&Round> {
+        self.rounds.iter().find(|round| round.kind == kind)
+    }
+    pub fn jeopardy(&self) -> Option<&Round> {
+        self.round(RoundKind::Standard)
+    }
+    pub fn double_jeopardy(&self) -> Option<&Round> {
+        self.round(RoundKind::Double)
+    }
+    pub fn final_jeopardy(&self) -> Option<&Round> {
+        self.round(RoundKind::Final)
+    }
+    pub fn tiebreaker(&self) -> Option<&Round> {
+        self.round(RoundKind::Tiebreaker)
+    }
+    /// Every media link in the game, once each, in the order the rounds
+    /// and boards list them.
+    pub fn media_urls(&self) -> Vec<&str> {
+        let mut urls: Vec<&str> = Vec::new();
+        let questions = self
+            .rounds
+            .iter()
+            .flat_map(|round| round.board.iter().flatten())
+            .filter_map(ClueSlot::clue);
+        for url in questions.flat_map(|q| &q.media) {
+            if !urls.contains(&&url[..]) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+}
+
+/// Downloads every game not already in the cache, fetching several at once.
+pub fn prefetch_games(
+    game_ids: &[usize],
+    cache: &Cache,
+    config: &BatchConfig,
+) -> Vec<Result<(), Error>> {
+    let cached: Vec<Result<bool, Error>> = game_ids
+        .iter()
+        .map(|&game_id| cache.read_page(game_id).map(|data| data.is_some()))
+        .collect();
+    let urls: Vec<String> = game_ids
+        .iter()
+        .zip(&cached)
+        .filter(|(_, cached)| matches!(cached, Ok(false)))
+        .map(|(&game_id, _)| gen_url(game_id))
+        .collect();
+    let urls: Vec<&str> = urls.iter().map(|url| &url[..]).collect();
+    let mut fetched = cache.fetcher().fetch_all(&urls, config).into_iter();
+
+    game_ids
+        .iter()
+        .zip(cached)
+        .map(|(&game_id, cached)| {
+            if cached? {
+                return Ok(());
+            }
+            let data = fetched.next().unwrap()?;
+            if is_error_page(&data) {
+                return Err(Error::GameNotFound(game_id));
+            }
+            cache.write_page(game_id, &data)
+        })
+        .collect()
+}
+
+/// Downloads one of a game's media files into the cache, unless it is
+/// already there, and returns where it is kept. j-archive media is fetched
+/// from [`base_url`], so it comes from a stand-in too when there is one.
+pub fn get_media(game_id: usize, url: &str, cache: &Cache) -> Result<PathBuf, Error> {
+    let path = cache.media_path(game_id, url);
+    if !path.exists() {
+        let data = cache.fetcher().fetch_bytes(&rebase_url(url))?;
+        cache.write_media(game_id, url, &data)?;
+    }
+    Ok(path)
+}
+
+/// Downloads all of a game's media into the cache, one file at a time.
+/// Returns one result per url of [`Game::media_urls`], in the same order.
+pub fn fetch_media(game: &Game, cache: &Cache) -> Vec<Result<PathBuf, Error>> {
+    let game_id = game.info.game_id;
+    game.media_urls()
+        .into_iter()
+        .map(|url| get_media(game_id, url, cache))
+        .collect()
+}
+
+/// Loads the game with a show number, finding it in the season listings.
+pub fn get_game_by_show_number(show_number: usize, cache: &Cache) -> Result<Game, Error> {
+    let listing = find_game_by_show_number(show_number, cache)?;
+    get_game_data_with_cache(listing.game_id(), cache)
+}
+
+/// Loads the game that aired on a date, finding it in the season listings.
+pub fn get_game_by_air_date(air_date: AirDate, cache: &Cache) -> Result<Game, Error> {
+    let listing = find_game_by_air_date(air_date, cache)?;
+    get_game_data_with_cache(listing.game_id(), cache)
+}
+
+/// Loads a game however it was named.
+pub fn get_game(key: GameKey, cache: &Cache) -> Result<Game, Error> {
+    match key {
+        GameKey::GameId(game_id) => get_game_data_with_cache(game_id, cache),
+        GameKey::ShowNumber(show_number) => get_game_by_show_number(show_number, cache),
+        GameKey::AirDate(air_date) => get_game_by_air_date(air_date, cache),
+    }
+}
+
+/// Loads a game from a j-archive game id, show number (`#4500`), air date
+/// (`2004-03-03`) or, failing those, a JSON game file.
+pub fn load_game(source: &str, cache: &Cache) -> Result<Game, Error> {
+    if let Ok(key) = source.parse::<GameKey>() {
+        return get_game(key, cache);
+    }
+    let data = fs::read_to_string(source).map_err(|e| Error::GameFile(source.into(), e))?;
+    Game::from_json(&data)
+}
+
+/// Loads a game from the default [`Cache`], fetching it if needed.
+pub fn get_game_data(game_id: usize) -> Result<Game, Error> {
+    get_game_data_with_cache(game_id, &Cache::default())
+}
+
+pub fn get_game_data_with_cache(game_id: usize, cache: &Cache) -> Result<Game, Error> {
     // missing in j-archive
     if [1132].contains(&game_id) {
-        return None;
+        return Err(Error::GameNotFound(game_id));
+    }
+
+    if let Some(game) = cache.read_parsed(game_id)? {
+        return Ok(game);
     }
 
-    let url = gen_url(game_id);
-    let data = match cache_read(&url) {
+    let data = match cache.read_page(game_id)? {
         Some(data) => {
             // println!("Loading {} from cache...", game_id);
             data
         }
         None => {
-            // respectful web scraping etiquette
-            // std::thread::sleep(std::time::Duration::from_secs(20));
-
+            // the fetcher's crawl policy keeps this polite
             // println!("Loading {} from j-archive...", game_id);
-            let data = get_webpage(&url);
-            // if game not in online database
-            if data.contains("ERROR") {
-                return None;
-            } else {
-                data
+            let data = cache.fetcher().fetch(&gen_url(game_id))?;
+            if is_error_page(&data) {
+                return Err(Error::GameNotFound(game_id));
             }
+            cache.write_page(game_id, &data)?;
+            data
         }
     };
 
-    cache_write(&url, &data);
+    let game = parse_game(game_id, &data)?;
+    cache.write_parsed(&game)?;
+    Ok(game)
+}
 
-    let mut board_1: Board = vec![vec![JeopardyQuestion::default(); 6]; 5];
-    let mut board_2: Board = vec![vec![JeopardyQuestion::default(); 6]; 5];
-    let mut categories = Vec::<Category>::new();
-    let mut final_jeopardy = JeopardyQuestion::default();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    populate_categories(&data, &mut categories);
-    populate_board(&data, &mut board_1, false);
-    populate_board(&data, &mut board_2, true);
-    populate_final_jeopardy(&data, &mut final_jeopardy);
+    #[test]
+    fn air_dates_must_be_real_months_and_days() {
+        assert_eq!("2004-03-03".parse(), Ok(AirDate::new(2004, 3, 3).unwrap()));
+        assert_eq!(
+            " 1984-9-10 ".parse(),
+            Ok(AirDate::new(1984, 9, 10).unwrap())
+        );
+        for s in [
+            "2004-13-03",
+            "2004-03-00",
+            "2004-03",
+            "2004/03/03",
+            "march 3",
+        ] {
+            assert_eq!(s.parse::<AirDate>(), Err(()), "{}", s);
+        }
+    }
 
-    return Some((categories, board_1, board_2, final_jeopardy));
+    #[test]
+    fn game_keys_are_told_apart() {
+        assert_eq!("1234".parse(), Ok(GameKey::GameId(1234)));
+        assert_eq!(" #4500".parse(), Ok(GameKey::ShowNumber(4500)));
+        assert_eq!(
+            "2004-03-03".parse(),
+            Ok(GameKey::AirDate(AirDate::new(2004, 3, 3).unwrap()))
+        );
+        for s in ["#", "#45a", "-1", "game.json", ""] {
+            assert_eq!(s.parse::<GameKey>(), Err(()), "{}", s);
+        }
+    }
 }
@@ -1,43 +1,240 @@
-use brrr::{get_game_data, print_board};
+use brrr::{
+    fetch_media, get_season_games, get_seasons, load_game, prefetch_games, print_board,
+    BatchConfig, Cache, CacheFetcher, FixtureFetcher, Game, MissingReason, RoundKind,
+};
 use std::env::args;
 
-fn cache_games(x: usize, y: usize) {
-    for game_id in x..y {
-        let game = match get_game_data(game_id) {
-            Some(x) => x,
-            None => {
-                println!("Game {} could not be loaded.", game_id);
-                continue;
-            }
+fn cache_games(x: usize, y: usize, cache: &Cache) {
+    let game_ids: Vec<usize> = (x..=y).collect();
+    let results = prefetch_games(&game_ids, cache, &BatchConfig::default());
+    for (game_id, result) in game_ids.iter().zip(results) {
+        if let Err(e) = result {
+            println!("Game {} could not be loaded: {}", game_id, e);
+        }
+    }
+}
+
+fn list_seasons(cache: &Cache) {
+    let seasons = match get_seasons(cache) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    for season in seasons {
+        print!("{}: {}", season.id(), season.name());
+        if let (Some(first), Some(last)) = (season.first_aired(), season.last_aired()) {
+            print!(", {} to {}", first, last);
+        }
+        if let Some(games) = season.games_archived() {
+            print!(" ({} games)", games);
+        }
+        println!();
+    }
+}
+
+fn list_season_games(season: &str, cache: &Cache) {
+    let games = match get_season_games(season, cache) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    for game in games {
+        print!("{}:", game.game_id());
+        if let Some(show_number) = game.show_number() {
+            print!(" show #{}", show_number);
+        }
+        if let Some(air_date) = game.air_date() {
+            print!(", aired {}", air_date);
+        }
+        println!(", {}", game.title());
+    }
+}
+
+/// Sums up how many clues have no text and why, such as `3 (2 unrevealed,
+/// 1 media only)`, or `None` if every clue is there.
+fn missing_clues(count: impl Fn(MissingReason) -> usize) -> Option<String> {
+    let reasons = [
+        (MissingReason::Unrevealed, "unrevealed"),
+        (MissingReason::NotArchived, "not archived"),
+        (MissingReason::MediaOnly, "media only"),
+    ];
+    let counts: Vec<String> = reasons
+        .iter()
+        .filter(|(reason, _)| count(*reason) > 0)
+        .map(|(reason, name)| format!("{} {}", count(*reason), name))
+        .collect();
+    let total: usize = reasons.iter().map(|(reason, _)| count(*reason)).sum();
+    if total == 0 {
+        None
+    } else {
+        Some(format!("{} ({})", total, counts.join(", ")))
+    }
+}
+
+fn print_game(game: &Game) {
+    let info = game.info();
+    print!("Game {}", info.game_id());
+    if let Some(show_number) = info.show_number() {
+        print!(", show #{}", show_number);
+    }
+    if let Some(air_date) = info.air_date() {
+        print!(", aired {}", air_date);
+    }
+    if let Some(season) = info.season() {
+        print!(", season {}", season);
+    }
+    println!();
+    if let Some(comments) = info.comments() {
+        println!("{}", comments);
+    }
+    for contestant in game.contestants() {
+        print!("{}", contestant.name());
+        if let Some(occupation) = contestant.occupation() {
+            print!(", {}", occupation);
+        }
+        if let Some(hometown) = contestant.hometown() {
+            print!(" from {}", hometown);
+        }
+        println!();
+    }
+
+    for round in game.rounds() {
+        let name = match round.kind() {
+            RoundKind::Standard => "First round",
+            RoundKind::Double => "Second round",
+            RoundKind::Final => "Final jeopardy",
+            RoundKind::Tiebreaker => "Tiebreaker",
         };
+        println!("{}: ", name);
+        let names: Vec<&str> = round.categories().iter().map(|c| c.name()).collect();
+        println!("Categories: {}", names.join(" "));
+        for category in round.categories() {
+            if let Some(comment) = category.comment() {
+                println!("{}: {}", category.name(), comment);
+            }
+        }
+        print_board(round.board());
+        for response in round.responses() {
+            println!("{:?}", response);
+        }
+        if let Some(missing) = missing_clues(|reason| round.count_missing(reason)) {
+            println!("Missing clues: {}", missing);
+        }
+    }
+    let total = |reason| {
+        game.rounds()
+            .iter()
+            .map(|round| round.count_missing(reason))
+            .sum()
+    };
+    if let Some(missing) = missing_clues(total) {
+        println!("Missing clues in the game: {}", missing);
+    }
+    for scores in game.scores() {
+        let scores: Vec<String> = scores
+            .scores()
+            .iter()
+            .map(|score| format!("{} {}", score.contestant(), score.score()))
+            .collect();
+        println!("Scores: {}", scores.join(", "));
     }
 }
 
 // TODO: parse args and add options
 fn main() {
-    let a: Vec<String> = args().collect();
-    let game_id: usize = a[1].parse().unwrap();
-
-    let game = match get_game_data(game_id) {
+    let mut a: Vec<String> = args().collect();
+    let cache = match a.iter().position(|arg| arg == "--cache-dir") {
+        Some(i) if i + 1 < a.len() => {
+            let dir = a.remove(i + 1);
+            a.remove(i);
+            Cache::new(dir).with_env_fetcher()
+        }
+        Some(_) => {
+            println!("Usage: {} --cache-dir <dir> ...", a[0]);
+            return;
+        }
+        None => Cache::default(),
+    };
+    let cache = match a.iter().position(|arg| arg == "--fixtures") {
+        Some(i) if i + 1 < a.len() => {
+            let dir = a.remove(i + 1);
+            a.remove(i);
+            cache.with_fetcher(FixtureFetcher::new(dir))
+        }
+        Some(_) => {
+            println!("Usage: {} --fixtures <dir> ...", a[0]);
+            return;
+        }
+        None => cache,
+    };
+    let cache = match a.iter().position(|arg| arg == "--offline") {
+        Some(i) => {
+            a.remove(i);
+            let fetcher = CacheFetcher::new(cache.dir());
+            cache.with_fetcher(fetcher)
+        }
+        None => cache,
+    };
+    if a.len() == 4 && a[1] == "--cache" {
+        match (a[2].parse(), a[3].parse()) {
+            (Ok(x), Ok(y)) => cache_games(x, y, &cache),
+            _ => println!("Usage: {} --cache <first id> <last id>", a[0]),
+        }
+        return;
+    }
+    if a.len() == 2 && a[1] == "--seasons" {
+        list_seasons(&cache);
+        return;
+    }
+    if a.len() == 3 && a[1] == "--season" {
+        list_season_games(&a[2], &cache);
+        return;
+    }
+    let json = a.iter().any(|arg| arg == "--json");
+    let media = a.iter().any(|arg| arg == "--media");
+    let source = match a.iter().skip(1).find(|arg| !arg.starts_with("--")) {
         Some(x) => x,
         None => {
-            println!("Game {} could not be loaded.", game_id);
+            println!(
+                "Usage: {} [--cache-dir <dir>] <game id | #show | air date | game.json> [--json] [--media]",
+                a[0]
+            );
+            println!(
+                "       {} [--cache-dir <dir>] --cache <first id> <last id>",
+                a[0]
+            );
+            println!("       {} [--cache-dir <dir>] --seasons", a[0]);
+            println!("       {} [--cache-dir <dir>] --season <season>", a[0]);
+            println!("Pages not in the cache are fetched from j-archive, unless --offline");
+            println!("is given or --fixtures <dir> names a fixture directory to use instead.");
             return;
         }
     };
 
-    let (categories, board_1, board_2, final_jeopardy) = game;
+    let game = match load_game(source, &cache) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
 
-    println!("Categories: ");
-    for c in categories {
-        print!("{} ", c);
+    if media {
+        let urls = game.media_urls();
+        for (url, result) in urls.iter().zip(fetch_media(&game, &cache)) {
+            if let Err(e) = result {
+                eprintln!("Media {} could not be downloaded: {}", url, e);
+            }
+        }
     }
-    println!("");
 
-    println!("First round: ");
-    print_board(&board_1);
-    println!("Second round: ");
-    print_board(&board_2);
-    println!("Final jeopardy: ");
-    println!("{:?}", final_jeopardy);
+    if json {
+        println!("{}", game.to_json());
+    } else {
+        print_game(&game);
+    }
 }
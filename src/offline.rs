@@ -0,0 +1,152 @@
+//! Fetchers that serve saved pages and never touch the network.
+//!
+//! Both map j-archive urls back to files: [`CacheFetcher`] reads a cache
+//! directory as [`Cache`] lays it out, and [`FixtureFetcher`] reads a
+//! directory laid out like `fixtures/`:
+//!
+//! ```text
+//! games/<game id>.html
+//! seasons/seasons.html
+//! seasons/season-<season>.html
+//! media/<file>
+//! ```
+//!
+//! A url with no saved file fails with [`Error::Offline`].
+
+use crate::cache::{listing_file, media_name};
+use crate::fetch::Fetcher;
+use crate::index::listing_name;
+use crate::{Cache, Error};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What a j-archive url points at.
+enum Page {
+    Game(usize),
+    Seasons,
+    Season(String),
+    Media,
+}
+
+fn query_param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let query = url.split('?').nth(1)?.split('#').next()?;
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        if key == name {
+            Some(value)
+        } else {
+            None
+        }
+    })
+}
+
+fn page_of(url: &str) -> Option<Page> {
+    let path = url.split(['?', '#']).next()?;
+    if path.ends_with("/showgame.php") {
+        query_param(url, "game_id")?.parse().ok().map(Page::Game)
+    } else if path.ends_with("/listseasons.php") {
+        Some(Page::Seasons)
+    } else if path.ends_with("/showseason.php") {
+        query_param(url, "season").map(|season| Page::Season(season.to_owned()))
+    } else if path.contains("/media/") {
+        Some(Page::Media)
+    } else {
+        None
+    }
+}
+
+fn offline(url: &str) -> Error {
+    Error::Offline(url.to_owned())
+}
+
+fn read_bytes(path: &Path, url: &str) -> Result<Vec<u8>, Error> {
+    match fs::read(path) {
+        Ok(data) => Ok(data),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(offline(url)),
+        Err(e) => Err(Error::Cache(e)),
+    }
+}
+
+/// Serves pages from a cache directory without ever writing to it. Media
+/// is found in whichever game's `media/` has a file of that name.
+#[derive(Clone, Debug)]
+pub struct CacheFetcher {
+    cache: Cache,
+}
+
+impl CacheFetcher {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> CacheFetcher {
+        CacheFetcher {
+            cache: Cache::new(dir),
+        }
+    }
+
+    fn find_media(&self, url: &str) -> Result<PathBuf, Error> {
+        let name = media_name(url);
+        let games = match fs::read_dir(self.cache.dir().join("games")) {
+            Ok(games) => games,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(offline(url)),
+            Err(e) => return Err(Error::Cache(e)),
+        };
+        games
+            .filter_map(|entry| Some(entry.ok()?.path().join("media").join(&name)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| offline(url))
+    }
+}
+
+impl Fetcher for CacheFetcher {
+    fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, Error> {
+        let data = match page_of(url).ok_or_else(|| offline(url))? {
+            Page::Game(game_id) => self.cache.read_page(game_id)?,
+            Page::Seasons => self.cache.read_listing(&listing_name(None))?.map(|l| l.0),
+            Page::Season(season) => self
+                .cache
+                .read_listing(&listing_name(Some(&season)))?
+                .map(|l| l.0),
+            Page::Media => return read_bytes(&self.find_media(url)?, url),
+        };
+        data.map(String::into_bytes).ok_or_else(|| offline(url))
+    }
+}
+
+/// Serves pages from a fixture directory such as the repository's
+/// `fixtures/`.
+#[derive(Clone, Debug)]
+pub struct FixtureFetcher {
+    dir: PathBuf,
+}
+
+impl FixtureFetcher {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> FixtureFetcher {
+        FixtureFetcher { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file a url is served from. It may not exist.
+    pub fn path_of(&self, url: &str) -> Option<PathBuf> {
+        let path = match page_of(url)? {
+            Page::Game(game_id) => self.dir.join("games").join(format!("{}.html", game_id)),
+            Page::Seasons => self
+                .dir
+                .join("seasons")
+                .join(listing_file(&listing_name(None))),
+            Page::Season(season) => self
+                .dir
+                .join("seasons")
+                .join(listing_file(&listing_name(Some(&season)))),
+            Page::Media => self.dir.join("media").join(media_name(url)),
+        };
+        Some(path)
+    }
+}
+
+impl Fetcher for FixtureFetcher {
+    fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, Error> {
+        read_bytes(&self.path_of(url).ok_or_else(|| offline(url))?, url)
+    }
+}
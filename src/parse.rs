@@ -0,0 +1,814 @@
+//! Building a [`Game`] from a j-archive game page.
+//!
+//! The page is parsed into a DOM and each round is read from its own
+//! container: the boards from `#jeopardy_round` and `#double_jeopardy_round`,
+//! and Final Jeopardy and any tiebreaker from their `table.final_round`.
+//! Clues are tied to their responses by element id (`clue_J_3_2`,
+//! `clue_DJ_1_5`, `clue_FJ`, `clue_TB`) rather than by where they happen to
+//! sit in the markup.
+//!
+//! j-archive has stored responses in two ways over the years (see
+//! [`Markup`]). Each page is checked for which one it uses, and a clue
+//! without a response is an error rather than an empty answer. The
+//! `fixtures/games` directory has a page of each kind.
+
+use crate::{
+    Attempt, Board, Category, ClueSlot, Contestant, Error, FinalResponse, Game, GameInfo,
+    JeopardyQuestion, MissingReason, RichText, Round, RoundKind, Score, ScoreTime, Scores,
+    TextStyle, DEFAULT_BASE_URL,
+};
+use ego_tree::iter::Edge;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref RE_TITLE: Regex = Regex::new(r#"Show #(\d+), aired (\d{4}-\d{2}-\d{2})"#).unwrap();
+    static ref TITLE: Selector = Selector::parse("title").unwrap();
+    static ref SEASON_LINK: Selector =
+        Selector::parse(r#"a[href*="showseason.php?season="]"#).unwrap();
+    static ref GAME_COMMENTS: Selector = Selector::parse("#game_comments").unwrap();
+    static ref CATEGORY: Selector = Selector::parse("td.category").unwrap();
+    static ref CATEGORY_NAME: Selector = Selector::parse("td.category_name").unwrap();
+    static ref CATEGORY_COMMENTS: Selector = Selector::parse("td.category_comments").unwrap();
+    static ref CLUE_CELL: Selector = Selector::parse("td.clue").unwrap();
+    static ref FINAL_ROUND: Selector = Selector::parse("table.final_round").unwrap();
+    static ref CLUE_TEXT: Selector = Selector::parse("td.clue_text[id]").unwrap();
+    static ref CLUE_VALUE: Selector =
+        Selector::parse("td.clue_value, td.clue_value_daily_double").unwrap();
+    static ref MEDIA_LINK: Selector = Selector::parse(r#"a[href*="/media/"]"#).unwrap();
+    static ref CLUE_ORDER: Selector = Selector::parse("td.clue_order_number").unwrap();
+    static ref TOGGLE: Selector = Selector::parse(r#"[onmouseover*="correct_response"]"#).unwrap();
+    static ref CORRECT_RESPONSE: Selector = Selector::parse("em.correct_response").unwrap();
+    static ref TABLE_CELL: Selector = Selector::parse("td").unwrap();
+    static ref SCORE_HEADING: Selector = Selector::parse("h3").unwrap();
+    static ref SCORE: Selector = Selector::parse("td.score_positive, td.score_negative").unwrap();
+    static ref SCORE_REMARKS: Selector = Selector::parse("td.score_remarks").unwrap();
+    static ref CONTESTANT: Selector = Selector::parse("p.contestants").unwrap();
+    static ref PLAYER_LINK: Selector = Selector::parse(r#"a[href*="player_id="]"#).unwrap();
+    static ref NICKNAME: Selector = Selector::parse("td.score_player_nickname").unwrap();
+}
+
+/// Visible text of an element, with runs of whitespace (and `<br>`s)
+/// collapsed to single spaces and emphasis tags kept as span styles.
+fn rich_text_of(element: ElementRef) -> RichText {
+    let mut text = RichText::new();
+    let (mut italic, mut underline, mut bold) = (0, 0, 0);
+    let mut space = false;
+    for edge in element.traverse() {
+        let (node, depth) = match edge {
+            Edge::Open(node) => (node, 1),
+            Edge::Close(node) => (node, -1),
+        };
+        match node.value() {
+            // the element's own tag (e.g. `em.correct_response`) is not emphasis
+            Node::Element(_) if node.id() == element.id() => {}
+            Node::Element(e) => match e.name() {
+                "i" | "em" => italic += depth,
+                "u" => underline += depth,
+                "b" | "strong" => bold += depth,
+                "br" => space = true,
+                _ => {}
+            },
+            Node::Text(t) if depth > 0 => {
+                let style = TextStyle::new(italic > 0, underline > 0, bold > 0);
+                for (i, word) in t.split(char::is_whitespace).enumerate() {
+                    space |= i > 0;
+                    if word.is_empty() {
+                        continue;
+                    }
+                    if space && !text.is_empty() {
+                        text.push_space();
+                    }
+                    text.push(word, style);
+                    space = false;
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+pub(crate) fn text_of(element: ElementRef) -> String {
+    rich_text_of(element).as_str().to_owned()
+}
+
+/// The single-quoted string arguments of an inline handler such as
+/// `toggle('clue_J_1_1', 'clue_J_1_1_stuck', '...')`, with JavaScript
+/// escapes undone.
+fn js_strings(script: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = script.chars();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut string = String::new();
+        loop {
+            match chars.next() {
+                Some('\\') => string.extend(chars.next()),
+                Some('\'') | None => break,
+                Some(c) => string.push(c),
+            }
+        }
+        strings.push(string);
+    }
+    strings
+}
+
+/// Splits a clue id such as `clue_DJ_3_2` into its round, column and row.
+fn clue_id(id: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = id.strip_prefix("clue_")?.split('_');
+    let (round, x, y) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((round, x, y))
+}
+
+/// Turns the 1-based column `x` and row `y` of a clue id into a position on
+/// the board.
+fn board_position(
+    game_id: usize,
+    board: &Board,
+    id: &str,
+    x: &str,
+    y: &str,
+) -> Result<(usize, usize), Error> {
+    let rows = board.len();
+    let columns = board.first().map_or(0, |row| row.len());
+    match (x.parse::<usize>(), y.parse::<usize>()) {
+        (Ok(x), Ok(y)) if (1..=columns).contains(&x) && (1..=rows).contains(&y) => {
+            Ok((x - 1, y - 1))
+        }
+        _ => Err(Error::parse(game_id, id, "clue is outside the board")),
+    }
+}
+
+/// Links to a clue's media, made absolute.
+fn media_urls(clue: ElementRef) -> Vec<String> {
+    clue.select(&MEDIA_LINK)
+        .filter_map(|a| a.value().attr("href"))
+        .map(|href| {
+            if href.contains("://") {
+                href.to_string()
+            } else {
+                format!("{}/{}", DEFAULT_BASE_URL, href.trim_start_matches('/'))
+            }
+        })
+        .collect()
+}
+
+/// Whether a clue's only text is inside links to its picture, sound or
+/// video, so there is nothing to read out without them.
+fn is_media_only(clue: ElementRef) -> bool {
+    let visible = |text: &str| text.chars().filter(|c| !c.is_whitespace()).count();
+    let in_links: usize = clue.select(&MEDIA_LINK).map(|a| visible(&text_of(a))).sum();
+    clue.select(&MEDIA_LINK).next().is_some() && visible(&text_of(clue)) == in_links
+}
+
+fn parse_dollars(text: &str) -> Option<usize> {
+    let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Where a page keeps its correct responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Markup {
+    /// Older pages keep them html-escaped inside the `onmouseover` handler
+    /// that reveals them: `toggle('clue_J_1_1', 'clue_J_1_1_stuck', '...')`.
+    MouseOver,
+    /// Newer pages put them in a hidden cell next to the clue, with the
+    /// clue's id plus `_r`: `<td id="clue_J_1_1_r" style="display:none;">`.
+    HiddenCell,
+}
+
+fn detect_markup(html: &Html) -> Option<Markup> {
+    let hidden_cell = html.select(&CLUE_TEXT).any(|element| {
+        let id = element.value().attr("id").unwrap_or("");
+        id.ends_with("_r") && element.select(&CORRECT_RESPONSE).next().is_some()
+    });
+    if hidden_cell {
+        Some(Markup::HiddenCell)
+    } else if html.select(&TOGGLE).next().is_some() {
+        Some(Markup::MouseOver)
+    } else {
+        None
+    }
+}
+
+/// What the page reveals about a clue once it is clicked.
+struct Response {
+    answer: RichText,
+    attempts: Vec<Attempt>,
+    triple_stumper: bool,
+    final_responses: Vec<FinalResponse>,
+}
+
+/// Reads the correct response and who rang in from the element that holds
+/// them. Attempts are listed in the order contestants rang in, with a
+/// pseudo-contestant "Triple Stumper" when nobody got it. In Final Jeopardy
+/// each name is followed by cells with the contestant's response and wager.
+fn read_response(container: ElementRef) -> Option<Response> {
+    let answer = rich_text_of(container.select(&CORRECT_RESPONSE).next()?);
+    let mut response = Response {
+        answer,
+        attempts: Vec::new(),
+        triple_stumper: false,
+        final_responses: Vec::new(),
+    };
+    for cell in container.select(&TABLE_CELL) {
+        let correct = cell.value().classes().any(|c| c == "right");
+        let wrong = cell.value().classes().any(|c| c == "wrong");
+        let text = text_of(cell);
+        if correct || wrong {
+            if text == "Triple Stumper" {
+                response.triple_stumper = true;
+            } else {
+                response.attempts.push(Attempt {
+                    contestant: text,
+                    correct,
+                });
+            }
+            continue;
+        }
+        let attempt = match response.attempts.last() {
+            Some(attempt) => attempt,
+            None => continue,
+        };
+        let finals = &mut response.final_responses;
+        if finals
+            .last()
+            .is_none_or(|f| f.contestant != attempt.contestant)
+        {
+            finals.push(FinalResponse {
+                contestant: attempt.contestant.clone(),
+                response: String::new(),
+                wager: None,
+                correct: attempt.correct,
+            });
+        }
+        let last = finals.last_mut().unwrap();
+        if text.starts_with('$') || text.starts_with("-$") {
+            last.wager = parse_dollars(&text);
+        } else {
+            last.response = text;
+        }
+    }
+    Some(response)
+}
+
+impl JeopardyQuestion {
+    fn set_response(&mut self, response: Response) {
+        self.answer = response.answer;
+        self.attempts = response.attempts;
+        self.triple_stumper = response.triple_stumper;
+    }
+}
+
+/// Responses in a round, keyed by clue id.
+fn responses(round: ElementRef, markup: Markup) -> HashMap<String, Response> {
+    let mut responses = HashMap::new();
+    match markup {
+        Markup::MouseOver => {
+            for element in round.select(&TOGGLE) {
+                let args = js_strings(element.value().attr("onmouseover").unwrap_or(""));
+                if args.len() < 3 {
+                    continue;
+                }
+                let fragment = Html::parse_fragment(&args[2]);
+                if let Some(response) = read_response(fragment.root_element()) {
+                    responses.insert(args[0].clone(), response);
+                }
+            }
+        }
+        Markup::HiddenCell => {
+            for element in round.select(&CLUE_TEXT) {
+                let id = element.value().attr("id").unwrap_or("");
+                if let (Some(id), Some(response)) = (id.strip_suffix("_r"), read_response(element))
+                {
+                    responses.insert(id.to_string(), response);
+                }
+            }
+        }
+    }
+    responses
+}
+
+/// Reads a category's title and the host's comment on it from its
+/// `td.category` cell.
+fn read_category(cell: ElementRef) -> Category {
+    let name = cell.select(&CATEGORY_NAME).next().map(text_of);
+    let comment = cell.select(&CATEGORY_COMMENTS).next().map(text_of);
+    Category {
+        name: name.unwrap_or_default(),
+        comment: comment.as_deref().and_then(non_empty),
+    }
+}
+
+/// Values have changed over the years ($100-$500 before November 2001), so
+/// daily doubles take their value from the rest of the board.
+fn fill_daily_double_values(board: &mut Board, double: bool) {
+    let mut units: Vec<usize> = board
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .filter_map(ClueSlot::clue)
+                .filter(|q| !q.daily_double && q.value != 0)
+                .map(move |q| q.value / (y + 1))
+        })
+        .collect();
+    units.sort_unstable();
+    let default_unit = if double { 400 } else { 200 };
+    let unit = units.get(units.len() / 2).copied().unwrap_or(default_unit);
+    for (y, row) in board.iter_mut().enumerate() {
+        let row_value = row
+            .iter()
+            .filter_map(ClueSlot::clue)
+            .filter(|q| !q.daily_double && q.value != 0)
+            .map(|q| q.value)
+            .max()
+            .unwrap_or(unit * (y + 1));
+        for slot in row.iter_mut() {
+            match slot {
+                ClueSlot::Clue(question) if question.daily_double => question.value = row_value,
+                _ => {}
+            }
+        }
+    }
+}
+
+fn round_div<'a>(html: &'a Html, div_id: &str) -> Option<ElementRef<'a>> {
+    let selector = Selector::parse(&format!("#{}", div_id)).unwrap();
+    html.select(&selector).next()
+}
+
+/// Reads a board round. Its size comes from the page: one column per
+/// category, and as many rows as it takes to hold every clue cell (empty
+/// cells included).
+///
+/// A cell with no clue text at all was never revealed, and a clue whose
+/// text is empty was played but not archived. A clue with only media links
+/// is kept with its media so it can still be played, unless j-archive has
+/// no correct response for it either.
+fn parse_board(
+    game_id: usize,
+    div: ElementRef,
+    markup: Markup,
+    kind: RoundKind,
+    prefix: &str,
+) -> Result<Round, Error> {
+    let location = div.value().id().unwrap_or("round").replace('_', " ");
+    let categories: Vec<Category> = div.select(&CATEGORY).map(read_category).collect();
+    if categories.is_empty() {
+        return Err(Error::parse(game_id, &location, "round has no categories"));
+    }
+    let columns = categories.len();
+    let rows = div.select(&CLUE_CELL).count().div_ceil(columns);
+    if rows == 0 {
+        return Err(Error::parse(game_id, &location, "round has no clues"));
+    }
+    let mut round = Round::new(kind, columns, rows);
+    round.categories = categories;
+
+    let mut responses = responses(div, markup);
+    for (i, cell) in div.select(&CLUE_CELL).enumerate() {
+        let clue = cell.select(&CLUE_TEXT).find_map(|element| {
+            let id = element.value().attr("id")?;
+            match clue_id(id) {
+                Some((round, x, y)) if round == prefix => Some((element, id, x, y)),
+                _ => None,
+            }
+        });
+        let (element, id, x, y) = match clue {
+            Some(clue) => clue,
+            None => {
+                // empty cells sit in board order
+                round.board[i / columns][i % columns] =
+                    ClueSlot::Missing(MissingReason::Unrevealed);
+                continue;
+            }
+        };
+        let (x, y) = board_position(game_id, &round.board, id, x, y)?;
+        let response = responses.remove(id);
+        let media_only = is_media_only(element);
+        let missing = if media_only && response.is_none() {
+            Some(MissingReason::MediaOnly)
+        } else if !media_only && text_of(element).is_empty() {
+            Some(MissingReason::NotArchived)
+        } else {
+            None
+        };
+        if let Some(reason) = missing {
+            round.board[y][x] = ClueSlot::Missing(reason);
+            continue;
+        }
+        let mut question = JeopardyQuestion {
+            x,
+            y,
+            clue: rich_text_of(element),
+            media: media_urls(element),
+            ..JeopardyQuestion::default()
+        };
+        let response =
+            response.ok_or_else(|| Error::parse(game_id, id, "clue has no correct response"))?;
+        question.set_response(response);
+        if let Some(order) = cell.select(&CLUE_ORDER).next() {
+            question.order = text_of(order).parse().ok();
+        }
+        if let Some(value) = cell.select(&CLUE_VALUE).next() {
+            if value
+                .value()
+                .classes()
+                .any(|c| c == "clue_value_daily_double")
+            {
+                // the daily double cell shows the wager, not the board value
+                question.daily_double = true;
+                question.wager = parse_dollars(&text_of(value));
+            } else {
+                question.value = parse_dollars(&text_of(value)).unwrap_or(0);
+            }
+        }
+        round.board[y][x] = ClueSlot::Clue(Box::new(question));
+    }
+
+    fill_daily_double_values(&mut round.board, kind == RoundKind::Double);
+    Ok(round)
+}
+
+/// Reads a single-clue round from its `table.final_round`: Final Jeopardy
+/// (`clue_FJ`) or a tiebreaker (`clue_TB`).
+fn parse_single_clue(
+    game_id: usize,
+    table: ElementRef,
+    markup: Markup,
+) -> Result<Option<Round>, Error> {
+    let (clue, kind) =
+        match table
+            .select(&CLUE_TEXT)
+            .find_map(|element| match element.value().attr("id")? {
+                "clue_FJ" => Some((element, RoundKind::Final)),
+                "clue_TB" => Some((element, RoundKind::Tiebreaker)),
+                _ => None,
+            }) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+    let id = clue.value().attr("id").unwrap();
+    let mut round = Round::new(kind, 1, 1);
+    round.categories = vec![table
+        .select(&CATEGORY)
+        .next()
+        .map(read_category)
+        .unwrap_or_default()];
+    let mut response = responses(table, markup)
+        .remove(id)
+        .ok_or_else(|| Error::parse(game_id, id, "clue has no correct response"))?;
+    round.responses = std::mem::take(&mut response.final_responses);
+    let mut question = JeopardyQuestion {
+        clue: rich_text_of(clue),
+        media: media_urls(clue),
+        ..JeopardyQuestion::default()
+    };
+    question.set_response(response);
+    round.board[0][0] = ClueSlot::Clue(Box::new(question));
+    Ok(Some(round))
+}
+
+pub(crate) fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+/// Splits what follows a contestant's name, such as `, a librarian from
+/// Portland, Oregon (whose 1-day cash winnings total $9,800)`, into
+/// occupation and hometown.
+fn split_description(description: &str) -> (Option<String>, Option<String>) {
+    let description = description.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    let description = match description.find(" (") {
+        Some(i) => &description[..i],
+        None => description,
+    };
+    let description = description
+        .strip_prefix("an ")
+        .or_else(|| description.strip_prefix("a "))
+        .unwrap_or(description);
+    match description.rfind(" from ") {
+        Some(i) => {
+            let occupation = description[..i].trim_end_matches(" originally");
+            (non_empty(occupation), non_empty(&description[i + 6..]))
+        }
+        None => (non_empty(description), None),
+    }
+}
+
+fn parse_contestants(html: &Html) -> Vec<Contestant> {
+    // attempts and scores name contestants by nickname, usually their first name
+    let nicknames: Vec<String> = html.select(&NICKNAME).map(text_of).collect();
+    html.select(&CONTESTANT)
+        .filter_map(|p| {
+            let link = p.select(&PLAYER_LINK).next()?;
+            let name = text_of(link);
+            let player_id = link
+                .value()
+                .attr("href")
+                .and_then(|href| href.split("player_id=").nth(1))
+                .and_then(|id| id.split('&').next())
+                .and_then(|id| id.parse().ok());
+            let text = text_of(p);
+            let (occupation, hometown) =
+                split_description(text.strip_prefix(&name[..]).unwrap_or(&text));
+            let first_name = name.split_whitespace().next().unwrap_or(&name).to_string();
+            let nickname = nicknames
+                .iter()
+                .find(|nickname| **nickname == first_name)
+                .or_else(|| {
+                    nicknames
+                        .iter()
+                        .find(|nickname| name.contains(&nickname[..]))
+                })
+                .cloned()
+                .unwrap_or(first_name);
+            Some(Contestant {
+                player_id,
+                name,
+                nickname,
+                occupation,
+                hometown,
+            })
+        })
+        .collect()
+}
+
+fn parse_score(text: &str) -> Option<isize> {
+    let amount = parse_dollars(text)? as isize;
+    if text.trim_start().starts_with('-') {
+        Some(-amount)
+    } else {
+        Some(amount)
+    }
+}
+
+fn score_time(heading: &str) -> Option<ScoreTime> {
+    if heading.starts_with("Scores at the first commercial break") {
+        Some(ScoreTime::FirstBreak)
+    } else if heading.starts_with("Scores at the end of the Jeopardy! Round") {
+        Some(ScoreTime::EndOfJeopardy)
+    } else if heading.starts_with("Scores at the end of the Double Jeopardy! Round") {
+        Some(ScoreTime::EndOfDoubleJeopardy)
+    } else if heading.starts_with("Final scores") {
+        Some(ScoreTime::Final)
+    } else {
+        None
+    }
+}
+
+/// Score tables, each introduced by an `<h3>` saying when it was taken and
+/// holding a row of nicknames, a row of scores and sometimes a row of
+/// remarks.
+fn parse_scores(html: &Html) -> Vec<Scores> {
+    let mut all_scores = Vec::new();
+    for heading in html.select(&SCORE_HEADING) {
+        let time = match score_time(&text_of(heading)) {
+            Some(time) => time,
+            None => continue,
+        };
+        let table = match heading.next_siblings().find_map(ElementRef::wrap) {
+            Some(table) if table.value().name() == "table" => table,
+            _ => continue,
+        };
+        let remarks: Vec<String> = table.select(&SCORE_REMARKS).map(text_of).collect();
+        let scores = table
+            .select(&NICKNAME)
+            .zip(table.select(&SCORE))
+            .enumerate()
+            .filter_map(|(i, (nickname, score))| {
+                Some(Score {
+                    contestant: text_of(nickname),
+                    score: parse_score(&text_of(score))?,
+                    remark: remarks.get(i).and_then(|remark| non_empty(remark)),
+                })
+            })
+            .collect();
+        all_scores.push(Scores { time, scores });
+    }
+    all_scores
+}
+
+fn parse_info(game_id: usize, html: &Html) -> GameInfo {
+    let mut info = GameInfo {
+        game_id,
+        ..GameInfo::default()
+    };
+    if let Some(title) = html.select(&TITLE).next() {
+        if let Some(caps) = RE_TITLE.captures(&text_of(title)) {
+            info.show_number = caps.get(1).unwrap().as_str().parse().ok();
+            info.air_date = caps.get(2).unwrap().as_str().parse().ok();
+        }
+    }
+    if let Some(link) = html.select(&SEASON_LINK).next() {
+        let href = link.value().attr("href").unwrap_or("");
+        let season = href.split("season=").nth(1).unwrap_or("");
+        let season = season.split('&').next().unwrap_or("");
+        if !season.is_empty() {
+            info.season = Some(season.to_string());
+        }
+    }
+    if let Some(comments) = html.select(&GAME_COMMENTS).next() {
+        let comments = text_of(comments);
+        if !comments.is_empty() {
+            info.comments = Some(comments);
+        }
+    }
+    info
+}
+
+/// Whether a page is j-archive's answer for a game it doesn't have. Only
+/// the error page itself counts: categories such as TRIAL & ERROR are upper
+/// case too.
+pub(crate) fn is_error_page(data: &str) -> bool {
+    data.contains("<title>J! Archive - ERROR</title>")
+        || data.contains("ERROR: No game in database")
+}
+
+/// Builds a game from the html of its j-archive page.
+pub fn parse_game(game_id: usize, data: &str) -> Result<Game, Error> {
+    let html = Html::parse_document(data);
+    let markup = detect_markup(&html)
+        .ok_or_else(|| Error::parse(game_id, "page", "no correct responses in any known markup"))?;
+
+    let mut rounds = Vec::new();
+    for &(div_id, kind, prefix) in &[
+        ("jeopardy_round", RoundKind::Standard, "J"),
+        ("double_jeopardy_round", RoundKind::Double, "DJ"),
+    ] {
+        if let Some(div) = round_div(&html, div_id) {
+            rounds.push(parse_board(game_id, div, markup, kind, prefix)?);
+        }
+    }
+    if rounds.is_empty() {
+        return Err(Error::parse(game_id, "page", "no rounds found"));
+    }
+    for table in html.select(&FINAL_ROUND) {
+        rounds.extend(parse_single_clue(game_id, table, markup)?);
+    }
+
+    Ok(Game {
+        info: parse_info(game_id, &html),
+        contestants: parse_contestants(&html),
+        scores: parse_scores(&html),
+        rounds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUSE_OVER: &str = include_str!("../fixtures/games/3001.html");
+    const HIDDEN_CELL: &str = include_str!("../fixtures/games/8801.html");
+
+    fn fixtures() -> Vec<(Markup, Game)> {
+        [(3001, MOUSE_OVER), (8801, HIDDEN_CELL)]
+            .iter()
+            .map(|&(game_id, data)| {
+                let markup = detect_markup(&Html::parse_document(data)).unwrap();
+                (markup, parse_game(game_id, data).unwrap())
+            })
+            .collect()
+    }
+
+    /// Answers of a round's board, `None` for spots with no clue.
+    fn answers(round: &Round) -> Vec<Vec<Option<&str>>> {
+        round
+            .board()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|slot| slot.clue().map(|q| q.answer()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_both_markups() {
+        let markups: Vec<Markup> = fixtures().into_iter().map(|(markup, _)| markup).collect();
+        assert_eq!(markups, [Markup::MouseOver, Markup::HiddenCell]);
+    }
+
+    #[test]
+    fn every_clue_has_its_answer() {
+        let games = fixtures();
+        for (_, game) in &games {
+            let kinds: Vec<RoundKind> = game.rounds().iter().map(|round| round.kind()).collect();
+            assert_eq!(
+                kinds,
+                [RoundKind::Standard, RoundKind::Double, RoundKind::Final]
+            );
+            for round in game.rounds() {
+                for answer in answers(round).into_iter().flatten().flatten() {
+                    assert!(
+                        !answer.is_empty(),
+                        "empty answer in game {}",
+                        game.info().game_id()
+                    );
+                }
+            }
+        }
+        let jeopardy = games[0].1.jeopardy().unwrap();
+        assert_eq!(answers(jeopardy)[0][0], Some("Paris"));
+        assert_eq!(answers(jeopardy)[2][1], Some("a kiwi"));
+        let (first, second) = (&games[0].1, &games[1].1);
+        for (a, b) in first.rounds().iter().zip(second.rounds()) {
+            assert_eq!(answers(a), answers(b));
+        }
+        let final_jeopardy = first.final_jeopardy().unwrap();
+        assert_eq!(answers(final_jeopardy)[0][0], Some("Mark Twain"));
+        let responses: Vec<&str> = final_jeopardy
+            .responses()
+            .iter()
+            .map(|r| r.response())
+            .collect();
+        assert_eq!(
+            responses,
+            ["Who is Melville?", "Who is Twain?", "Who is Mark Twain?"]
+        );
+    }
+    #[test]
+    fn daily_doubles_keep_their_wagers() {
+        for (_, game) in fixtures() {
+            let daily_doubles: Vec<(RoundKind, usize, usize, Option<usize>)> = game
+                .rounds()
+                .iter()
+                .flat_map(|round| {
+                    round
+                        .board()
+                        .iter()
+                        .flatten()
+                        .filter_map(ClueSlot::clue)
+                        .filter(|q| q.is_daily_double())
+                        .map(move |q| (round.kind(), q.x, q.y, q.wager()))
+                })
+                .collect();
+            assert_eq!(
+                daily_doubles,
+                [
+                    (RoundKind::Standard, 3, 3, Some(400)),
+                    (RoundKind::Double, 1, 3, Some(1000)),
+                    (RoundKind::Double, 4, 3, Some(600)),
+                ]
+            );
+        }
+    }
+    #[test]
+    fn only_the_error_page_means_no_such_game() {
+        assert!(is_error_page(
+            "<html><head><title>J! Archive - ERROR</title></head></html>"
+        ));
+        assert!(!is_error_page(&MOUSE_OVER.replacen(
+            "WORLD CAPITALS",
+            "TRIAL &amp; ERROR",
+            1
+        )));
+        assert!(!is_error_page(HIDDEN_CELL));
+    }
+    #[test]
+    fn unrevealed_clues_are_missing() {
+        for (_, game) in fixtures() {
+            let missing: Vec<(RoundKind, usize, usize, MissingReason)> = game
+                .rounds()
+                .iter()
+                .flat_map(|round| {
+                    round.board().iter().enumerate().flat_map(move |(y, row)| {
+                        row.iter().enumerate().filter_map(move |(x, slot)| {
+                            slot.missing().map(|reason| (round.kind(), x, y, reason))
+                        })
+                    })
+                })
+                .collect();
+            assert_eq!(
+                missing,
+                [
+                    (RoundKind::Standard, 5, 4, MissingReason::Unrevealed),
+                    (RoundKind::Double, 1, 4, MissingReason::Unrevealed),
+                ]
+            );
+        }
+    }
+    #[test]
+    fn media_links_are_kept() {
+        for (_, game) in fixtures() {
+            assert_eq!(
+                game.media_urls(),
+                ["http://www.j-archive.com/media/1997-09-15_J_8.jpg"]
+            );
+        }
+    }
+}
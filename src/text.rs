@@ -0,0 +1,142 @@
+//! Clue and response text with the emphasis j-archive gives it.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Emphasis on a run of text. j-archive often underlines or italicizes the
+/// key word of a clue, and italicizes titles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextStyle {
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    italic: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    underline: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    bold: bool,
+}
+
+impl TextStyle {
+    pub fn new(italic: bool, underline: bool, bold: bool) -> TextStyle {
+        TextStyle {
+            italic,
+            underline,
+            bold,
+        }
+    }
+    pub fn italic(&self) -> bool {
+        self.italic
+    }
+    pub fn underline(&self) -> bool {
+        self.underline
+    }
+    pub fn bold(&self) -> bool {
+        self.bold
+    }
+    pub fn is_plain(&self) -> bool {
+        *self == TextStyle::default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StyledSpan {
+    text: String,
+    #[serde(flatten)]
+    style: TextStyle,
+}
+
+impl StyledSpan {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn style(&self) -> TextStyle {
+        self.style
+    }
+}
+
+/// Text split into runs of one style each. Adjacent runs always differ in
+/// style, and the plain text is kept alongside for [`RichText::as_str`].
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct RichText {
+    plain: String,
+    spans: Vec<StyledSpan>,
+}
+
+impl RichText {
+    pub fn new() -> RichText {
+        RichText::default()
+    }
+
+    /// The text with all styling dropped.
+    pub fn as_str(&self) -> &str {
+        &self.plain
+    }
+
+    pub fn spans(&self) -> &[StyledSpan] {
+        &self.spans
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plain.is_empty()
+    }
+
+    /// Whether any part of the text is styled.
+    pub fn is_styled(&self) -> bool {
+        self.spans.iter().any(|span| !span.style.is_plain())
+    }
+
+    pub fn push(&mut self, text: &str, style: TextStyle) {
+        if text.is_empty() {
+            return;
+        }
+        self.plain.push_str(text);
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(StyledSpan {
+                text: text.to_owned(),
+                style,
+            }),
+        }
+    }
+
+    /// Appends a space in the style of whatever came before it, so a space
+    /// between a plain and an underlined word is not underlined.
+    pub(crate) fn push_space(&mut self) {
+        let style = self.spans.last().map(|span| span.style).unwrap_or_default();
+        self.push(" ", style);
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(text: &str) -> RichText {
+        let mut rich = RichText::new();
+        rich.push(text, TextStyle::default());
+        rich
+    }
+}
+
+impl From<Vec<StyledSpan>> for RichText {
+    fn from(spans: Vec<StyledSpan>) -> RichText {
+        let mut rich = RichText::new();
+        for span in spans {
+            rich.push(&span.text, span.style);
+        }
+        rich
+    }
+}
+
+impl fmt::Display for RichText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.plain)
+    }
+}
+
+impl fmt::Debug for RichText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_styled() {
+            f.debug_list().entries(&self.spans).finish()
+        } else {
+            fmt::Debug::fmt(&self.plain, f)
+        }
+    }
+}